@@ -11,6 +11,7 @@ Set-Location $STAGE
 $ZIP = "$SRC_DIR\$($env:CRATE_NAME)-$($env:APPVEYOR_REPO_TAG_NAME)-$($env:TARGET).zip"
 
 Copy-Item "$SRC_DIR\target\$($env:TARGET)\release\course_list_generator.exe" '.\'
+Copy-Item "$SRC_DIR\target\$($env:TARGET)\release\course_list_cli.exe" '.\'
 
 7z a "$ZIP" *
 
@@ -23,6 +23,7 @@ main() {
     test -f Cargo.lock || cargo generate-lockfile
 
     build course_list_generator
+    build course_list_cli
 
     cd $stage
     tar czf $src/$CRATE_NAME-$TRAVIS_TAG-$TARGET.tar.gz *
@@ -0,0 +1,138 @@
+//! Lessons of courses taking place once a week
+
+use crate::workbook::parse_date;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Day or range of days without lessons like `24.12.2020` or
+/// `21.12.2020-06.01.2021`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Holiday {
+    pub first: NaiveDate,
+    pub last: NaiveDate,
+}
+
+impl Holiday {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.first <= date && date <= self.last
+    }
+}
+
+impl FromStr for Holiday {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(date) = parse_date(s) {
+            return Ok(Self {
+                first: date,
+                last: date,
+            });
+        }
+
+        // dates like `2020-12-24` contain dashes as well
+        let (first, last) = s
+            .match_indices('-')
+            .find_map(|(i, _)| Some((parse_date(&s[..i])?, parse_date(&s[i + 1..])?)))
+            .ok_or_else(|| format!("Expected a date or a range of dates but got `{}`", s))?;
+
+        if last < first {
+            return Err(format!("Holidays `{}` end before they start", s));
+        }
+
+        Ok(Self { first, last })
+    }
+}
+
+impl fmt::Display for Holiday {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.first.format("%d.%m.%Y"))?;
+        if self.last != self.first {
+            write!(f, "-{}", self.last.format("%d.%m.%Y"))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses holidays separated by commas, semicolons or line breaks
+pub fn parse_holidays(s: &str) -> Result<Vec<Holiday>, String> {
+    s.split(&[',', ';', '\n'][..])
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Dates of `lessons` lessons on `weekday` starting at `start`
+///
+/// Lessons falling on one of the `holidays` are moved to the following week.
+pub fn lesson_dates(
+    start: NaiveDate,
+    weekday: Weekday,
+    lessons: usize,
+    holidays: &[Holiday],
+) -> Vec<NaiveDate> {
+    let offset = (7 + weekday.num_days_from_monday() - start.weekday().num_days_from_monday()) % 7;
+    let mut date = start + Duration::days(offset.into());
+    let mut dates = Vec::with_capacity(lessons);
+
+    while dates.len() < lessons {
+        if !holidays.iter().any(|holiday| holiday.contains(date)) {
+            dates.push(date);
+        }
+        date += Duration::weeks(1);
+    }
+
+    dates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn holidays() {
+        let date = |d, m, y| NaiveDate::from_ymd(y, m, d);
+
+        assert_eq!(
+            "24.12.2020".parse(),
+            Ok(Holiday {
+                first: date(24, 12, 2020),
+                last: date(24, 12, 2020)
+            })
+        );
+        let christmas: Holiday = "2020-12-21-2021-01-06".parse().unwrap();
+        assert_eq!(christmas.to_string(), "21.12.2020-06.01.2021");
+        assert!(christmas.contains(date(1, 1, 2021)));
+        assert!("06.01.2021-21.12.2020".parse::<Holiday>().is_err());
+        assert!("Weihnachten".parse::<Holiday>().is_err());
+
+        assert_eq!(
+            parse_holidays("24.12.2020; 21.12.2020-06.01.2021,\n").unwrap(),
+            vec!["24.12.2020".parse().unwrap(), christmas]
+        );
+    }
+
+    #[test]
+    fn lessons() {
+        let date = |d, m, y| NaiveDate::from_ymd(y, m, d);
+        let holidays = vec!["21.12.2020-06.01.2021".parse().unwrap()];
+
+        // the course starts on a Thursday, lessons are on Mondays
+        assert_eq!(
+            lesson_dates(date(10, 12, 2020), Weekday::Mon, 4, &holidays),
+            vec![
+                date(14, 12, 2020),
+                date(11, 1, 2021),
+                date(18, 1, 2021),
+                date(25, 1, 2021),
+            ]
+        );
+        assert_eq!(
+            lesson_dates(date(14, 12, 2020), Weekday::Mon, 1, &[]),
+            vec![date(14, 12, 2020)]
+        );
+        assert!(lesson_dates(date(14, 12, 2020), Weekday::Mon, 0, &[]).is_empty());
+    }
+}
@@ -0,0 +1,549 @@
+use anyhow::Result;
+use chrono::{NaiveDate, Weekday};
+use directories::ProjectDirs;
+use flexi_logger::{colored_default_format, detailed_format, Duplicate, Logger};
+use log::{error, info, warn};
+use sir::{
+    attendance::Holiday,
+    course_list::{
+        statistics::{attendance_report, AttendanceError, AttendanceSheets},
+        CourseList, CourseListError, CourseListOptions,
+    },
+    output::{writer_for, OutputError},
+    preferences::{
+        read_preferences, AuxiliaryColumn, AuxiliaryFormat, BaseColumns, CsvEncoding, CsvQuoting,
+        GroupSplit, ListKind, Orientation, OutputFormat, PhoneFormat, Preferences, SortKey,
+    },
+    workbook::{parse_date, WorkbookError, WorkbookManager},
+};
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+use thiserror::Error;
+
+#[derive(Debug, StructOpt)]
+#[structopt(about = "Generate course lists from the booking workbook of Schimmen-Im-Ried.de")]
+pub struct Opt {
+    #[structopt(subcommand)]
+    pub command: Subcommand,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Subcommand {
+    /// Generate a course list without opening the window
+    ///
+    /// Settings are taken from the preferences stored by the window unless a
+    /// preference file is given. Every option overrides the matching preference.
+    ///
+    /// Exit codes: 0 on success, 2 for missing or invalid settings, 3 if the
+    /// course list could not be read and 4 if it could not be written.
+    Generate(Box<GenerateArgs>),
+
+    /// Report the attendance of filled-in sign-in sheets
+    ///
+    /// Reads every sheet of the given XLSX files written as attendance lists
+    /// and writes the rate of attendance per participant and per group. Lessons
+    /// without any mark are left out, empty cells of other lessons count as
+    /// missed.
+    ///
+    /// Exit codes: 0 on success, 2 for invalid settings, 3 if the sheets could
+    /// not be read and 4 if the report could not be written.
+    Attendance(AttendanceArgs),
+}
+
+#[derive(Debug, StructOpt)]
+pub struct AttendanceArgs {
+    /// Preference file to read the CSV and page settings from
+    #[structopt(short, long, parse(from_os_str))]
+    pub preferences: Option<PathBuf>,
+
+    /// Filled-in sign-in sheets
+    #[structopt(required = true, parse(from_os_str))]
+    pub sources: Vec<PathBuf>,
+
+    /// Path of the report
+    #[structopt(short, long)]
+    pub destination: String,
+
+    /// Format of the report, one of auto, xlsx or csv
+    #[structopt(short, long)]
+    pub format: Option<OutputFormat>,
+
+    /// Note participants who missed this many lessons one after another, 0
+    /// turns it off
+    #[structopt(long, default_value = "3")]
+    pub missed_in_row: usize,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct GenerateArgs {
+    /// Preference file to read the settings from
+    #[structopt(short, long, parse(from_os_str))]
+    pub preferences: Option<PathBuf>,
+
+    /// Path to the source workbook
+    #[structopt(short, long)]
+    pub source: Option<String>,
+
+    /// Name of the sheet in the source workbook
+    #[structopt(long)]
+    pub sheet: Option<String>,
+
+    /// Columns containing the group of a participant, e.g. P, P:U or P,R:T
+    #[structopt(short, long)]
+    pub column: Option<String>,
+
+    /// Row of the header in the source sheet starting at 1 [default: detected]
+    #[structopt(long)]
+    pub header_row: Option<usize>,
+
+    /// Path to the course list that will be written
+    #[structopt(short, long)]
+    pub destination: Option<String>,
+
+    /// Format of the course list, one of auto, xlsx or csv
+    #[structopt(short, long)]
+    pub format: Option<OutputFormat>,
+
+    /// Field delimiter of csv files
+    #[structopt(long)]
+    pub delimiter: Option<char>,
+
+    /// Quoting of csv fields, one of necessary, always, non-numeric or never
+    #[structopt(long)]
+    pub quoting: Option<CsvQuoting>,
+
+    /// Encoding of csv files, one of utf-8, utf-8-bom or windows-1252
+    #[structopt(long)]
+    pub encoding: Option<CsvEncoding>,
+
+    /// Split the list by group into sheets or files, one of off, sheets or files
+    ///
+    /// When splitting into files the destination is a directory.
+    #[structopt(long)]
+    pub split_groups: Option<GroupSplit>,
+
+    /// Format of telephone numbers, one of national or international
+    #[structopt(long)]
+    pub phone_format: Option<PhoneFormat>,
+
+    /// Base columns in their order like "name, id=Nr., telephone" out of id,
+    /// group, name, telephone and email with optional header labels
+    #[structopt(long)]
+    pub base_columns: Option<BaseColumns>,
+
+    /// Page orientation of XLSX lists, one of portrait or landscape
+    #[structopt(long)]
+    pub orientation: Option<Orientation>,
+
+    /// Write the summary below the list
+    #[structopt(long, overrides_with = "no-summary")]
+    pub summary: bool,
+
+    /// Leave out the summary below the list
+    #[structopt(long, overrides_with = "summary")]
+    pub no_summary: bool,
+
+    /// Write the price read from this column with totals
+    #[structopt(long)]
+    pub price_column: Option<String>,
+
+    /// Sort by name, group (and name), surname, id or an additional column
+    /// by its position like aux:1
+    #[structopt(long)]
+    pub sort: Option<SortKey>,
+
+    /// Sort in descending order
+    #[structopt(long, overrides_with = "no-descending")]
+    pub descending: bool,
+
+    /// Sort in ascending order
+    #[structopt(long, overrides_with = "descending")]
+    pub no_descending: bool,
+
+    /// Leave out invalid email addresses, e.g. for mailing lists
+    #[structopt(long, overrides_with = "no-exclude-invalid-emails")]
+    pub exclude_invalid_emails: bool,
+
+    /// Keep invalid email addresses as they were written
+    #[structopt(long, overrides_with = "exclude-invalid-emails")]
+    pub no_exclude_invalid_emails: bool,
+
+    /// Leave out rows which can not be read instead of failing
+    #[structopt(long, overrides_with = "no-skip-invalid-rows")]
+    pub skip_invalid_rows: bool,
+
+    /// Fail on rows which can not be read
+    #[structopt(long, overrides_with = "skip-invalid-rows")]
+    pub no_skip_invalid_rows: bool,
+
+    /// Additional column as NAME=COLUMN or NAME=COLUMN:FORMAT with the format
+    /// text, number or date, may be given multiple times
+    #[structopt(
+        short,
+        long = "auxiliary",
+        number_of_values = 1,
+        parse(try_from_str = parse_auxiliary)
+    )]
+    pub auxiliaries: Vec<AuxiliaryColumn>,
+
+    /// Write sign-in sheets per group with a column for every lesson
+    #[structopt(long)]
+    pub attendance: bool,
+
+    /// First day of the course for attendance lists like 14.09.2020
+    #[structopt(long, parse(try_from_str = parse_day))]
+    pub course_start: Option<NaiveDate>,
+
+    /// Weekday of the lessons like mon or tuesday
+    #[structopt(long, parse(try_from_str = parse_weekday))]
+    pub weekday: Option<Weekday>,
+
+    /// Number of lessons of the course
+    #[structopt(long)]
+    pub lessons: Option<usize>,
+
+    /// Day or range of days without lessons like 24.12.2020 or
+    /// 21.12.2020-06.01.2021, may be given multiple times
+    #[structopt(long = "holiday", number_of_values = 1)]
+    pub holidays: Vec<Holiday>,
+}
+
+/// Setting turned on by `on` and off by `off` or `None` if neither is given
+fn flag(on: bool, off: bool) -> Option<bool> {
+    match (on, off) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_day(s: &str) -> Result<NaiveDate, String> {
+    parse_date(s).ok_or_else(|| format!("Expected a date like 14.09.2020 but got `{}`", s))
+}
+
+fn parse_weekday(s: &str) -> Result<Weekday, String> {
+    s.parse()
+        .map_err(|_| format!("Expected a weekday like mon but got `{}`", s))
+}
+
+fn parse_auxiliary(s: &str) -> Result<AuxiliaryColumn, String> {
+    let mut parts = s.splitn(2, '=');
+
+    match (parts.next(), parts.next()) {
+        (Some(name), Some(column)) if !column.is_empty() => {
+            let mut column = column.splitn(2, ':');
+            Ok(AuxiliaryColumn {
+                name: name.to_string(),
+                column: column.next().unwrap_or_default().to_string(),
+                format: column
+                    .next()
+                    .map_or(Ok(AuxiliaryFormat::Text), str::parse)?,
+            })
+        }
+        _ => Err(format!("Expected NAME=COLUMN but got `{}`", s)),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum HeadlessError {
+    #[error("Could not load preferences: {0}")]
+    Preferences(#[source] anyhow::Error),
+
+    #[error("No {0} given")]
+    MissingSetting(&'static str),
+
+    #[error("Could not open spreadsheet file: {0}")]
+    OpenWorkbook(#[source] WorkbookError),
+
+    #[error("Could not read course list: {0}")]
+    Read(#[source] CourseListError),
+
+    #[error("Could not write course list: {0}")]
+    Write(#[source] CourseListError),
+
+    #[error("Could not write attendance report: {0}")]
+    WriteReport(#[source] OutputError),
+
+    #[error("Could not read attendance from `{0}`: {1}")]
+    ReadAttendance(String, #[source] AttendanceError),
+}
+
+impl HeadlessError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Preferences(_) | Self::MissingSetting(_) => 2,
+            Self::OpenWorkbook(_) | Self::Read(_) | Self::ReadAttendance(..) => 3,
+            Self::Write(_) | Self::WriteReport(_) => 4,
+        }
+    }
+}
+
+pub fn start_logger(proj_dirs: &ProjectDirs) -> Result<()> {
+    Logger::with_env_or_str("info")
+        .log_to_file()
+        .directory(proj_dirs.data_dir().join("log"))
+        .discriminant("headless")
+        .duplicate_to_stderr(Duplicate::Info)
+        .format_for_files(detailed_format)
+        .format_for_stderr(colored_default_format)
+        .start()?;
+
+    Ok(())
+}
+
+/// Runs the generator without a window and returns the exit code of the process
+pub fn run(command: Subcommand) -> u8 {
+    let result = match command {
+        Subcommand::Generate(args) => generate(*args),
+        Subcommand::Attendance(args) => report_attendance(args),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(err) => {
+            error!("{}", err);
+            err.exit_code()
+        }
+    }
+}
+
+fn read_preferences_from(path: Option<&PathBuf>) -> Result<Preferences, HeadlessError> {
+    match path {
+        Some(path) => {
+            info!("Loading preferences from `{}`", path.display());
+            Preferences::from_path(path)
+        }
+        None => read_preferences(),
+    }
+    .map_err(|err| HeadlessError::Preferences(err))
+}
+
+fn load_preferences(args: &GenerateArgs) -> Result<Preferences, HeadlessError> {
+    let mut prefs = read_preferences_from(args.preferences.as_ref())?;
+
+    if let Some(source) = &args.source {
+        prefs.src_path = source.clone();
+    }
+    if let Some(sheet) = &args.sheet {
+        prefs.src_sheet = sheet.clone();
+    }
+    if let Some(column) = &args.column {
+        prefs.src_column = column.clone();
+    }
+    if let Some(header_row) = args.header_row {
+        prefs.columns.header_row = Some(header_row);
+    }
+    if let Some(destination) = &args.destination {
+        prefs.dest_path = destination.clone();
+    }
+    if let Some(format) = args.format {
+        prefs.dest_format = format;
+    }
+    if let Some(delimiter) = args.delimiter {
+        prefs.csv.delimiter = delimiter;
+    }
+    if let Some(quoting) = args.quoting {
+        prefs.csv.quoting = quoting;
+    }
+    if let Some(encoding) = args.encoding {
+        prefs.csv.encoding = encoding;
+    }
+    if let Some(split_groups) = args.split_groups {
+        prefs.split_groups = split_groups;
+    }
+    if let Some(phone_format) = args.phone_format {
+        prefs.phone_format = phone_format;
+    }
+    if let Some(base_columns) = &args.base_columns {
+        prefs.base_columns = base_columns.clone();
+    }
+    if let Some(orientation) = args.orientation {
+        prefs.page.orientation = orientation;
+    }
+    if let Some(summary) = flag(args.summary, args.no_summary) {
+        prefs.hide_summary = !summary;
+    }
+    if let Some(price_column) = &args.price_column {
+        prefs.show_price = true;
+        prefs.price_column = price_column.clone();
+    }
+    if let Some(key) = args.sort {
+        prefs.sort.key = key;
+    }
+    if let Some(descending) = flag(args.descending, args.no_descending) {
+        prefs.sort.descending = descending;
+    }
+    if let Some(exclude) = flag(args.exclude_invalid_emails, args.no_exclude_invalid_emails) {
+        prefs.exclude_invalid_emails = exclude;
+    }
+    if let Some(skip) = flag(args.skip_invalid_rows, args.no_skip_invalid_rows) {
+        prefs.skip_invalid_rows = skip;
+    }
+    if !args.auxiliaries.is_empty() {
+        prefs.auxiliary_columns = args.auxiliaries.clone();
+    }
+    if args.attendance {
+        prefs.list_kind = ListKind::Attendance;
+    }
+    if let Some(start) = args.course_start {
+        prefs.attendance.start = Some(start);
+    }
+    if let Some(weekday) = args.weekday {
+        prefs.attendance.weekday = weekday;
+    }
+    if let Some(lessons) = args.lessons {
+        prefs.attendance.lessons = lessons;
+    }
+    if !args.holidays.is_empty() {
+        prefs.attendance.holidays = args.holidays.clone();
+    }
+
+    Ok(prefs)
+}
+
+fn generate(args: GenerateArgs) -> Result<(), HeadlessError> {
+    let prefs = load_preferences(&args)?;
+
+    for (value, name) in &[
+        (&prefs.src_path, "source"),
+        (&prefs.src_sheet, "sheet"),
+        (&prefs.src_column, "column"),
+        (&prefs.dest_path, "destination"),
+    ] {
+        if value.is_empty() {
+            return Err(HeadlessError::MissingSetting(name));
+        }
+    }
+    if prefs.list_kind == ListKind::Attendance && prefs.attendance.start.is_none() {
+        return Err(HeadlessError::MissingSetting("course start"));
+    }
+
+    let options = CourseListOptions::from(&prefs);
+
+    let mut workbook_manager = WorkbookManager::new();
+    workbook_manager
+        .open(&prefs.src_path)
+        .map_err(|err| HeadlessError::OpenWorkbook(err))?;
+
+    info!(
+        "Reading course list from `{}` (sheet: {}, column: {})",
+        prefs.src_path, prefs.src_sheet, prefs.src_column
+    );
+    let list = workbook_manager
+        .read_course_list(&prefs.src_sheet, &prefs.src_column, &options)
+        .map_err(|err| HeadlessError::Read(err))?;
+    info!("Found header in row {} ({})", list.header_row, list.header);
+    for issue in &list.issues {
+        if issue.is_error() {
+            warn!("{} (left out)", issue);
+        } else {
+            warn!("{}", issue);
+        }
+    }
+
+    let count = list.entries.len();
+
+    WorkbookManager::write_course_list(&prefs.dest_path, list.entries, &options)
+        .map_err(|err| HeadlessError::Write(err))?;
+
+    info!(
+        "Successfully wrote data of {} participants to {}",
+        count, prefs.dest_path
+    );
+
+    Ok(())
+}
+
+fn report_attendance(args: AttendanceArgs) -> Result<(), HeadlessError> {
+    let mut prefs = read_preferences_from(args.preferences.as_ref())?;
+    if let Some(format) = args.format {
+        prefs.dest_format = format;
+    }
+    let options = CourseListOptions::from(&prefs);
+
+    let mut participants = Vec::new();
+    for source in &args.sources {
+        info!("Reading attendance from `{}`", source.display());
+
+        let mut workbook_manager = WorkbookManager::new();
+        workbook_manager
+            .open(source)
+            .map_err(|err| HeadlessError::OpenWorkbook(err))?;
+        participants.extend(
+            workbook_manager
+                .read_attendance()
+                .map_err(|err| HeadlessError::ReadAttendance(source.display().to_string(), err))?,
+        );
+    }
+
+    if args.missed_in_row > 0 {
+        for participant in &participants {
+            let missed = participant.missed_in_row();
+            if missed >= args.missed_in_row {
+                warn!(
+                    "{} ({}) missed {} lessons in a row",
+                    participant.name, participant.group, missed
+                );
+            }
+        }
+    }
+
+    let format = options.format.resolve(&args.destination);
+    writer_for(format, &options.csv, options.page)
+        .write(
+            Path::new(&args.destination),
+            &[attendance_report(&participants, args.missed_in_row)],
+        )
+        .map_err(|err| HeadlessError::WriteReport(err))?;
+
+    info!(
+        "Successfully wrote attendance of {} participants to {}",
+        participants.len(),
+        args.destination
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate_args(args: &[&str]) -> GenerateArgs {
+        let args = ["course_list_cli", "generate"].iter().chain(args);
+        match Opt::from_iter_safe(args).unwrap().command {
+            Subcommand::Generate(args) => *args,
+            other => panic!("expected generate but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn override_flags() {
+        let path = std::env::temp_dir().join("sir_override_flags.ron");
+        let prefs = Preferences {
+            hide_summary: true,
+            skip_invalid_rows: true,
+            ..Preferences::default()
+        };
+        std::fs::write(&path, ron::ser::to_string(&prefs).unwrap()).unwrap();
+        let path = path.to_str().unwrap();
+
+        let prefs = load_preferences(&generate_args(&["-p", path])).unwrap();
+        assert!(prefs.hide_summary && prefs.skip_invalid_rows);
+        assert!(!prefs.sort.descending);
+
+        let prefs = load_preferences(&generate_args(&[
+            "-p",
+            path,
+            "--summary",
+            "--no-skip-invalid-rows",
+            "--no-descending",
+            "--descending",
+        ]))
+        .unwrap();
+        assert!(!prefs.hide_summary && !prefs.skip_invalid_rows);
+        assert!(prefs.sort.descending);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,26 @@
+//! Course list generator for the command line
+//!
+//! `course_list_generator` is a windows application without a console, so
+//! scripts use this binary to see its output and exit code.
+
+use cli::Opt;
+use sir::get_proj_dirs;
+use std::process::ExitCode;
+use structopt::StructOpt;
+
+mod cli;
+
+fn main() -> ExitCode {
+    let opt = Opt::from_args();
+
+    let started = get_proj_dirs().and_then(|proj_dirs| cli::start_logger(&proj_dirs));
+    if let Err(err) = started {
+        eprintln!("Could not start logging: {:#}", err);
+        return ExitCode::from(2);
+    }
+
+    let code = cli::run(opt.command);
+    log::logger().flush();
+
+    ExitCode::from(code)
+}
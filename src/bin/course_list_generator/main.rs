@@ -4,21 +4,29 @@
 #![windows_subsystem = "windows"]
 
 use anyhow::{Error, Result};
-use course_list::{CourseList, CourseListOptions};
 use flexi_logger::{colored_detailed_format, detailed_format, Logger};
 use iced::{
-    button, executor, text_input, window, Align, Application, Button, Column, Command, Element,
-    Length, Row, Settings, Space, Text, TextInput,
+    button, executor, scrollable, text_input, window, Align, Application, Button, Checkbox, Color,
+    Column, Command, Element, Length, Radio, Row, Scrollable, Settings, Space, Text, TextInput,
 };
-use log::{error, info};
+use log::{error, info, warn};
 use sir::{
+    attendance::parse_holidays,
+    course_list::{
+        preview_table, sort_course_list, CourseList, CourseListOptions, ParsedCourseList,
+    },
     get_proj_dirs,
-    preferences::{load_preferences, store_preferences, Preferences},
+    preferences::{
+        load_preferences, store_preferences, used_auxiliary_columns, AttendanceOptions,
+        AuxiliaryColumn, BaseColumn, BaseColumns, BaseField, ColumnMapping, GroupSplit, ListKind,
+        Orientation, Preferences, SortKey,
+    },
     update,
+    workbook::parse_date,
     workbook::WorkbookManager,
+    ColumnName,
 };
-
-mod course_list;
+use std::{num::ParseIntError, path::Path};
 
 fn main() -> Result<()> {
     let proj_dirs = get_proj_dirs()?;
@@ -43,7 +51,7 @@ fn main() -> Result<()> {
     info!("Starting window");
     Main::run(Settings {
         window: window::Settings {
-            size: (900, 300),
+            size: (900, 760),
             resizable: false,
             ..window::Settings::default()
         },
@@ -56,6 +64,7 @@ fn main() -> Result<()> {
 #[derive(Debug, Copy, Clone)]
 enum State {
     Entry,
+    Preview,
     Error,
     Result,
 }
@@ -71,19 +80,51 @@ enum Message {
     Nothing,
 
     SrcPathInputChanged(String),
-    SrcSheetInputChanged(String),
+    SrcSheetSelected(usize),
     SrcColumnInputChanged(String),
+    HeaderColumnPressed(usize),
+    HeaderRowInputChanged(String),
 
     DestPathInputChanged(String),
+    SplitGroupsChanged(GroupSplit),
+    OrientationChanged(Orientation),
+    FitToWidthToggled(bool),
+    RepeatHeaderToggled(bool),
+    HeaderFooterToggled(bool),
+    GridlinesToggled(bool),
+    FreezeHeaderToggled(bool),
+    SkipInvalidRowsToggled(bool),
+    ExcludeInvalidEmailsToggled(bool),
+    ShowPriceToggled(bool),
+    ShowSummaryToggled(bool),
+    PriceColumnInputChanged(String),
+    SortKeyPressed,
+    SortDescendingToggled(bool),
+    ListKindChanged(ListKind),
+    CourseStartInputChanged(String),
+    WeekdayPressed,
+    LessonsInputChanged(String),
+    HolidaysInputChanged(String),
 
     GeneratePressed,
+    ConfirmPressed,
     BackPressed,
 
+    BaseColumnToggled { id: usize, shown: bool },
+    BaseLabelInputChanged { id: usize, value: String },
+    BaseUpPressed(usize),
+    BaseDownPressed(usize),
+
     AuxNameInputChanged { id: usize, value: String },
     AuxColInputChanged { id: usize, value: String },
+    AuxFormatPressed(usize),
+    AuxAddPressed,
+    AuxRemovePressed(usize),
+    AuxUpPressed(usize),
+    AuxDownPressed(usize),
 
     //GenerateCourseList,
-    LoadPreferences(Preferences),
+    LoadPreferences(Box<Preferences>),
     StorePreferences(Result<(), String>),
 }
 
@@ -92,8 +133,9 @@ struct Main {
     src_path_input: text_input::State,
     src_path_text: String,
 
-    src_sheet_input: text_input::State,
     src_sheet_text: String,
+    sheet_names: Vec<String>,
+    sheet_scroll: scrollable::State,
 
     src_column_input: text_input::State,
     src_column_text: String,
@@ -101,23 +143,342 @@ struct Main {
     dest_path_input: text_input::State,
     dest_path_text: String,
 
+    price_column_input: text_input::State,
+
+    sort_button: button::State,
+
+    course_start_input: text_input::State,
+    course_start_text: String,
+    weekday_button: button::State,
+    lessons_input: text_input::State,
+    lessons_text: String,
+    holidays_input: text_input::State,
+    holidays_text: String,
+
     generate_button: button::State,
+    confirm_button: button::State,
     back_button: button::State,
 
-    aux_name_input: Vec<text_input::State>,
-    aux_name_text: Vec<String>,
-    aux_col_input: Vec<text_input::State>,
-    aux_col_text: Vec<String>,
+    base_columns: Vec<BaseColumnInput>,
+    base_scroll: scrollable::State,
+
+    auxiliaries: Vec<AuxiliaryInput>,
+    aux_add_button: button::State,
+    aux_scroll: scrollable::State,
+
+    header_row_input: text_input::State,
+    header_row_text: String,
+
+    /// Labels of the header row of the selected sheet by column
+    header_labels: Vec<(usize, String)>,
+    header_buttons: Vec<button::State>,
+    header_scroll: scrollable::State,
+    header_text: String,
+
+    /// Settings which can not be changed in the window
+    preferences: Preferences,
 
     error_text: String,
     result_text: String,
 
+    /// Course list read by the last generate waiting to be confirmed
+    preview: Option<(ParsedCourseList, CourseListOptions)>,
+    preview_scroll: scrollable::State,
+
     state: State,
 
     workbook_manager: WorkbookManager,
 }
 
-const AUXILIARIES: usize = 4;
+/// Inputs of a single base column
+struct BaseColumnInput {
+    column: BaseColumn,
+    shown: bool,
+    label_input: text_input::State,
+    up_button: button::State,
+    down_button: button::State,
+}
+
+impl BaseColumnInput {
+    fn new(column: BaseColumn, shown: bool) -> Self {
+        Self {
+            column,
+            shown,
+            label_input: text_input::State::default(),
+            up_button: button::State::default(),
+            down_button: button::State::default(),
+        }
+    }
+
+    /// Inputs of all base columns, the shown ones first in their order
+    fn all(shown: &BaseColumns) -> Vec<Self> {
+        shown
+            .iter()
+            .map(|column| Self::new(column.clone(), true))
+            .chain(
+                BaseField::ALL
+                    .iter()
+                    .filter(|&&field| !shown.contains(field))
+                    .map(|&field| Self::new(BaseColumn::new(field), false)),
+            )
+            .collect()
+    }
+
+    fn view(&mut self, id: usize, last: bool) -> Row<'_, Message> {
+        let up = Button::new(&mut self.up_button, Text::new("Up").size(14));
+        let down = Button::new(&mut self.down_button, Text::new("Down").size(14));
+        let field = self.column.field;
+
+        Row::new()
+            .align_items(Align::Center)
+            .spacing(10)
+            .push(
+                Checkbox::new(self.shown, field.to_string(), move |shown| {
+                    Message::BaseColumnToggled { id, shown }
+                })
+                .width(Length::Units(110)),
+            )
+            .push(
+                TextInput::new(
+                    &mut self.label_input,
+                    field.default_label(),
+                    &self.column.label,
+                    move |value| Message::BaseLabelInputChanged { id, value },
+                )
+                .padding(5)
+                .width(Length::Units(110)),
+            )
+            .push(match id {
+                0 => up,
+                _ => up.on_press(Message::BaseUpPressed(id)),
+            })
+            .push(match last {
+                true => down,
+                false => down.on_press(Message::BaseDownPressed(id)),
+            })
+    }
+}
+
+/// Inputs of a single additional column
+#[derive(Default)]
+struct AuxiliaryInput {
+    column: AuxiliaryColumn,
+    name_input: text_input::State,
+    col_input: text_input::State,
+    format_button: button::State,
+    up_button: button::State,
+    down_button: button::State,
+    remove_button: button::State,
+}
+
+impl AuxiliaryInput {
+    fn new(column: AuxiliaryColumn) -> Self {
+        Self {
+            column,
+            ..Self::default()
+        }
+    }
+
+    fn view(&mut self, id: usize, last: bool) -> Row<'_, Message> {
+        let up = Button::new(&mut self.up_button, Text::new("Up").size(14));
+        let down = Button::new(&mut self.down_button, Text::new("Down").size(14));
+
+        Row::new()
+            .align_items(Align::Center)
+            .spacing(10)
+            .push(
+                TextInput::new(
+                    &mut self.col_input,
+                    "XX",
+                    &self.column.column,
+                    move |value| Message::AuxColInputChanged { id, value },
+                )
+                .padding(5)
+                .width(Length::Units(40)),
+            )
+            .push(
+                TextInput::new(
+                    &mut self.name_input,
+                    "Name",
+                    &self.column.name,
+                    move |value| Message::AuxNameInputChanged { id, value },
+                )
+                .padding(5)
+                .width(Length::Units(120)),
+            )
+            .push(
+                Button::new(
+                    &mut self.format_button,
+                    Text::new(self.column.format.to_string()).size(14),
+                )
+                .on_press(Message::AuxFormatPressed(id))
+                .width(Length::Units(70)),
+            )
+            .push(match id {
+                0 => up,
+                _ => up.on_press(Message::AuxUpPressed(id)),
+            })
+            .push(match last {
+                true => down,
+                false => down.on_press(Message::AuxDownPressed(id)),
+            })
+            .push(
+                Button::new(&mut self.remove_button, Text::new("Remove").size(14))
+                    .on_press(Message::AuxRemovePressed(id)),
+            )
+    }
+}
+
+/// Number of header labels per row in the header preview
+const HEADER_COLUMNS: usize = 6;
+
+impl Main {
+    fn header_row(&self) -> Result<Option<usize>, ParseIntError> {
+        match self.header_row_text.trim() {
+            "" => Ok(None),
+            text => text.parse().map(Some),
+        }
+    }
+
+    /// Parses the inputs of the attendance list
+    fn attendance_options(&self) -> Result<AttendanceOptions, String> {
+        let start = match self.course_start_text.trim() {
+            "" => None,
+            text => Some(parse_date(text).ok_or_else(|| format!("Invalid date `{}`", text))?),
+        };
+        let lessons = self
+            .lessons_text
+            .trim()
+            .parse()
+            .map_err(|err| format!("Invalid number of lessons: {}", err))?;
+
+        Ok(AttendanceOptions {
+            start,
+            weekday: self.preferences.attendance.weekday,
+            lessons,
+            holidays: parse_holidays(&self.holidays_text)?,
+        })
+    }
+
+    /// Opens the source workbook to list its sheets
+    fn load_workbook(&mut self) {
+        self.sheet_names.clear();
+
+        // do not try to open every path while it is typed
+        if Path::new(&self.src_path_text).is_file() {
+            match self.workbook_manager.open(&self.src_path_text) {
+                Ok(()) => {
+                    self.sheet_names = self
+                        .workbook_manager
+                        .sheets()
+                        .map(<[String]>::to_vec)
+                        .unwrap_or_default()
+                }
+                Err(err) => warn!(
+                    "Could not open workbook (path: {}): {}",
+                    self.src_path_text, err
+                ),
+            }
+        }
+
+        self.load_header();
+    }
+
+    /// Reads the header row of the selected sheet
+    fn load_header(&mut self) {
+        self.header_labels.clear();
+        self.header_text.clear();
+
+        if self.sheet_names.contains(&self.src_sheet_text) {
+            let mapping = ColumnMapping {
+                header_row: self.header_row().ok().flatten(),
+                ..self.preferences.columns.clone()
+            };
+
+            match self
+                .workbook_manager
+                .read_header(&self.src_sheet_text, &mapping)
+            {
+                Ok((row, labels)) => {
+                    self.header_text = format!("Header in row {}, click to select columns", row);
+                    self.header_labels = labels;
+                }
+                Err(err) => self.header_text = format!("{}", err),
+            }
+        }
+
+        self.header_buttons = vec![button::State::default(); self.header_labels.len()];
+    }
+
+    /// Shown base columns, empty labels are replaced by the default label
+    fn base_columns(&self) -> BaseColumns {
+        BaseColumns(
+            self.base_columns
+                .iter()
+                .filter(|input| input.shown)
+                .map(|input| match input.column.label.trim() {
+                    "" => BaseColumn::new(input.column.field),
+                    _ => input.column.clone(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Additional columns as entered, blank ones are left out by `used_auxiliary_columns`
+    fn auxiliary_columns(&self) -> Vec<AuxiliaryColumn> {
+        self.auxiliaries
+            .iter()
+            .map(|aux| aux.column.clone())
+            .collect()
+    }
+
+    /// Swaps the additional column at `index` with the following one
+    ///
+    /// Sorting by one of them keeps sorting by the same column.
+    fn swap_auxiliaries(&mut self, index: usize) {
+        self.auxiliaries.swap(index, index + 1);
+        self.preferences.sort.key = match self.preferences.sort.key {
+            SortKey::Auxiliary(i) if i == index => SortKey::Auxiliary(index + 1),
+            SortKey::Auxiliary(i) if i == index + 1 => SortKey::Auxiliary(index),
+            key => key,
+        };
+    }
+
+    /// Description of the sort key shown on its button
+    fn sort_label(&self) -> String {
+        match self.preferences.sort.key {
+            SortKey::Name => "Name".to_string(),
+            SortKey::GroupName => "Group and name".to_string(),
+            SortKey::Surname => "Surname".to_string(),
+            SortKey::Id => "Customer id".to_string(),
+            SortKey::Auxiliary(index) => match self.auxiliaries.get(index) {
+                Some(aux) if !aux.column.name.is_empty() => aux.column.name.clone(),
+                Some(aux) => aux.column.column.clone(),
+                None => "Name".to_string(),
+            },
+        }
+    }
+
+    /// Adds the column to the group columns or removes it if it is listed
+    fn toggle_column(&mut self, column: usize) {
+        let name = column.to_column_name();
+        let mut columns: Vec<String> = self
+            .src_column_text
+            .split(',')
+            .map(|column| column.trim().to_string())
+            .filter(|column| !column.is_empty())
+            .collect();
+
+        match columns.iter().position(|column| *column == name) {
+            Some(index) => {
+                columns.remove(index);
+            }
+            None => columns.push(name),
+        }
+
+        self.src_column_text = columns.join(", ");
+    }
+}
 
 impl Application for Main {
     type Message = Message;
@@ -127,14 +488,11 @@ impl Application for Main {
     fn new(_flags: ()) -> (Self, Command<Self::Message>) {
         (
             Self {
-                aux_name_text: vec![String::new(); AUXILIARIES],
-                aux_name_input: vec![text_input::State::default(); AUXILIARIES],
-                aux_col_text: vec![String::new(); AUXILIARIES],
-                aux_col_input: vec![text_input::State::default(); AUXILIARIES],
+                base_columns: BaseColumnInput::all(&BaseColumns::default()),
                 ..Self::default()
             },
             Command::perform(load_preferences(), |prefs| match prefs {
-                Ok(prefs) => Message::LoadPreferences(prefs),
+                Ok(prefs) => Message::LoadPreferences(Box::new(prefs)),
                 Err(err) => {
                     error!("Could not load preferences: {}", err);
                     Message::Nothing
@@ -157,12 +515,77 @@ impl Application for Main {
         match message {
             Nothing => {}
 
-            SrcPathInputChanged(s) => self.src_path_text = s,
-            SrcSheetInputChanged(s) => self.src_sheet_text = s,
+            SrcPathInputChanged(s) => {
+                self.src_path_text = s;
+                self.load_workbook();
+            }
+            SrcSheetSelected(index) => {
+                self.src_sheet_text = self.sheet_names[index].clone();
+                self.load_header();
+            }
             SrcColumnInputChanged(s) => self.src_column_text = s,
+            HeaderColumnPressed(column) => self.toggle_column(column),
+            HeaderRowInputChanged(s) => {
+                self.header_row_text = s;
+                self.load_header();
+            }
             DestPathInputChanged(s) => self.dest_path_text = s,
+            SplitGroupsChanged(split) => self.preferences.split_groups = split,
+            OrientationChanged(orientation) => self.preferences.page.orientation = orientation,
+            FitToWidthToggled(fit) => self.preferences.page.fit_to_width = fit,
+            RepeatHeaderToggled(repeat) => self.preferences.page.repeat_header = repeat,
+            HeaderFooterToggled(show) => self.preferences.page.header_footer = show,
+            GridlinesToggled(show) => self.preferences.page.gridlines = show,
+            FreezeHeaderToggled(freeze) => self.preferences.page.freeze_header = freeze,
+            SkipInvalidRowsToggled(skip) => self.preferences.skip_invalid_rows = skip,
+            ExcludeInvalidEmailsToggled(exclude) => {
+                self.preferences.exclude_invalid_emails = exclude
+            }
+            ShowPriceToggled(show) => self.preferences.show_price = show,
+            ShowSummaryToggled(show) => self.preferences.hide_summary = !show,
+            PriceColumnInputChanged(s) => self.preferences.price_column = s,
+            SortKeyPressed => {
+                let sort = &mut self.preferences.sort;
+                sort.key = sort.key.next(self.auxiliaries.len());
+            }
+            SortDescendingToggled(descending) => self.preferences.sort.descending = descending,
+            ListKindChanged(kind) => self.preferences.list_kind = kind,
+            CourseStartInputChanged(s) => self.course_start_text = s,
+            WeekdayPressed => {
+                let weekday = &mut self.preferences.attendance.weekday;
+                *weekday = weekday.succ();
+            }
+            LessonsInputChanged(s) => self.lessons_text = s,
+            HolidaysInputChanged(s) => self.holidays_text = s,
 
             GeneratePressed => {
+                self.preferences.columns.header_row = match self.header_row() {
+                    Ok(row) => row,
+                    Err(err) => {
+                        self.error_text = format!("Invalid header row: {}", err);
+                        error!("Invalid header row `{}`: {}", self.header_row_text, err);
+                        self.state = State::Error;
+                        return Command::none();
+                    }
+                };
+
+                if self.preferences.list_kind == ListKind::Attendance {
+                    self.preferences.attendance = match self.attendance_options() {
+                        Ok(attendance) if attendance.start.is_none() => {
+                            self.error_text = "No first day of the course given".to_string();
+                            self.state = State::Error;
+                            return Command::none();
+                        }
+                        Ok(attendance) => attendance,
+                        Err(err) => {
+                            self.error_text = format!("Invalid attendance list: {}", err);
+                            error!("Invalid attendance list: {}", err);
+                            self.state = State::Error;
+                            return Command::none();
+                        }
+                    };
+                }
+
                 if let Err(err) = self.workbook_manager.open(&self.src_path_text) {
                     let err = Error::new(err);
                     self.error_text = format!("Could not open spreadsheet file: {:?}", err);
@@ -174,23 +597,32 @@ impl Application for Main {
                     return Command::none();
                 }
 
-                let auxiliaries = self
-                    .aux_name_text
-                    .clone()
-                    .into_iter()
-                    .zip(self.aux_col_text.clone().into_iter())
-                    .filter(|(name, col)| !(name.is_empty() && col.is_empty()))
-                    .map(|(name, col)| (name, col))
-                    .collect();
-                let options = &CourseListOptions {
-                    show_price: false,
+                // the sort key points into the additional columns shown
+                let (auxiliaries, sort) =
+                    used_auxiliary_columns(&self.auxiliary_columns(), self.preferences.sort);
+                let options = CourseListOptions {
+                    show_price: self.preferences.show_price,
+                    price_column: self.preferences.price_column.clone(),
                     auxiliaries,
+                    columns: self.preferences.columns.clone(),
+                    base_columns: self.base_columns(),
+                    sort,
+                    format: self.preferences.dest_format,
+                    csv: self.preferences.csv.clone(),
+                    page: self.preferences.page,
+                    split_groups: self.preferences.split_groups,
+                    skip_invalid_rows: self.preferences.skip_invalid_rows,
+                    phone_format: self.preferences.phone_format,
+                    exclude_invalid_emails: self.preferences.exclude_invalid_emails,
+                    hide_summary: self.preferences.hide_summary,
+                    list_kind: self.preferences.list_kind,
+                    attendance: self.preferences.attendance.clone(),
                 };
 
                 let mut list = match self.workbook_manager.read_course_list(
                     &self.src_sheet_text,
                     &self.src_column_text,
-                    options,
+                    &options,
                 ) {
                     Ok(l) => l,
                     Err(err) => {
@@ -201,16 +633,26 @@ impl Application for Main {
                     }
                 };
 
-                list.sort();
+                sort_course_list(&mut list.entries, options.sort);
+                self.preview = Some((list, options));
+                self.state = State::Preview;
+            }
+            ConfirmPressed => {
+                let (list, options) = match self.preview.take() {
+                    Some(preview) => preview,
+                    None => return Command::none(),
+                };
 
                 self.result_text = format!(
-                    "Successfully wrote data of {} participants to {}",
-                    list.len(),
-                    self.dest_path_text
+                    "Successfully wrote data of {} participants to {}\n\nHeader in row {}: {}",
+                    list.entries.len(),
+                    self.dest_path_text,
+                    list.header_row,
+                    list.header
                 );
 
                 if let Err(err) =
-                    WorkbookManager::write_course_list(&self.dest_path_text, list, options)
+                    WorkbookManager::write_course_list(&self.dest_path_text, list.entries, &options)
                 {
                     self.error_text = format!("Could not write course list: {:#?}", err);
                     error!(
@@ -229,41 +671,76 @@ impl Application for Main {
                         src_sheet: self.src_sheet_text.clone(),
                         src_column: self.src_column_text.clone(),
                         dest_path: self.dest_path_text.clone(),
-                        auxiliaries: Some(
-                            self.aux_name_text
-                                .clone()
-                                .into_iter()
-                                .zip(self.aux_col_text.clone().into_iter())
-                                .collect(),
-                        ),
+                        base_columns: self.base_columns(),
+                        auxiliary_columns: options.auxiliaries.clone(),
+                        sort: options.sort,
+                        ..self.preferences.clone()
                     }),
                     |result| Message::StorePreferences(result.map_err(|err| format!("{}", err))),
                 );
             }
             BackPressed => match self.state {
-                State::Error | State::Result => self.state = State::Entry,
+                State::Preview | State::Error | State::Result => {
+                    self.preview = None;
+                    self.state = State::Entry;
+                }
                 _ => {}
             },
 
-            AuxNameInputChanged { id, value } => self.aux_name_text[id] = value,
-            AuxColInputChanged { id, value } => self.aux_col_text[id] = value,
+            BaseColumnToggled { id, shown } => self.base_columns[id].shown = shown,
+            BaseLabelInputChanged { id, value } => self.base_columns[id].column.label = value,
+            BaseUpPressed(id) => self.base_columns.swap(id - 1, id),
+            BaseDownPressed(id) => self.base_columns.swap(id, id + 1),
+
+            AuxNameInputChanged { id, value } => self.auxiliaries[id].column.name = value,
+            AuxColInputChanged { id, value } => self.auxiliaries[id].column.column = value,
+            AuxFormatPressed(id) => {
+                let column = &mut self.auxiliaries[id].column;
+                column.format = column.format.next();
+            }
+            AuxAddPressed => self.auxiliaries.push(AuxiliaryInput::default()),
+            AuxRemovePressed(id) => {
+                self.auxiliaries.remove(id);
+                self.preferences.sort.key = match self.preferences.sort.key {
+                    SortKey::Auxiliary(index) if index == id => SortKey::Name,
+                    SortKey::Auxiliary(index) if index > id => SortKey::Auxiliary(index - 1),
+                    key => key,
+                };
+            }
+            AuxUpPressed(id) => self.swap_auxiliaries(id - 1),
+            AuxDownPressed(id) => self.swap_auxiliaries(id),
 
             LoadPreferences(prefs) => {
+                self.preferences = *prefs.clone();
                 self.src_path_text = prefs.src_path.to_string();
                 self.src_sheet_text = prefs.src_sheet.to_string();
                 self.src_column_text = prefs.src_column.to_string();
                 self.dest_path_text = prefs.dest_path.to_string();
-
-                if let Some(auxiliaries) = prefs.auxiliaries {
-                    let (mut aux_name_text, mut aux_col_text): (Vec<String>, Vec<String>) =
-                        auxiliaries.into_iter().unzip();
-
-                    aux_name_text.resize(AUXILIARIES, String::new());
-                    aux_col_text.resize(AUXILIARIES, String::new());
-
-                    self.aux_name_text = aux_name_text;
-                    self.aux_col_text = aux_col_text;
-                }
+                self.header_row_text = prefs
+                    .columns
+                    .header_row
+                    .map(|row| row.to_string())
+                    .unwrap_or_default();
+                self.course_start_text = prefs
+                    .attendance
+                    .start
+                    .map(|date| date.format("%d.%m.%Y").to_string())
+                    .unwrap_or_default();
+                self.lessons_text = prefs.attendance.lessons.to_string();
+                self.holidays_text = prefs
+                    .attendance
+                    .holidays
+                    .iter()
+                    .map(|holiday| holiday.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                self.base_columns = BaseColumnInput::all(&prefs.base_columns);
+                self.auxiliaries = prefs
+                    .auxiliary_columns
+                    .into_iter()
+                    .map(AuxiliaryInput::new)
+                    .collect();
+                self.load_workbook();
             }
             StorePreferences(result) => {
                 if let Err(err) = result {
@@ -284,35 +761,66 @@ impl Application for Main {
 
         match self.state {
             Entry => {
-                let mut auxiliaries = (0..AUXILIARIES)
-                    .zip(self.aux_name_input.iter_mut())
-                    .zip(self.aux_name_text.iter())
-                    .zip(self.aux_col_input.iter_mut())
-                    .zip(self.aux_col_text.iter())
-                    .map(|((((id, name_input), name_text), col_state), col_text)| {
-                        Row::new()
-                            .align_items(Align::Start)
-                            .spacing(10)
-                            .push(
-                                TextInput::new(col_state, "XX", col_text, move |value| {
-                                    Message::AuxColInputChanged { id, value }
-                                })
-                                .padding(5)
-                                .width(Length::Units(30)),
-                            )
-                            .push(
-                                TextInput::new(name_input, "Name", name_text, move |value| {
-                                    Message::AuxNameInputChanged { id, value }
-                                })
-                                .padding(5)
-                                .width(Length::Units(100)),
-                            )
-                            .into()
-                    })
-                    .collect::<Vec<Element<_>>>();
+                let sort_label = self.sort_label();
+                let page = self.preferences.page;
+                let list_kind = self.preferences.list_kind;
+                let weekday = self.preferences.attendance.weekday.to_string();
+                let last = self.base_columns.len().saturating_sub(1);
+                let base_columns = self.base_columns.iter_mut().enumerate().fold(
+                    Scrollable::new(&mut self.base_scroll).spacing(5),
+                    |rows, (id, input)| rows.push(input.view(id, id == last)),
+                );
 
-                auxiliaries.insert(0, Text::new("Additional Columns").into());
-                auxiliaries.insert(1, Space::new(Length::Fill, Length::Units(10)).into());
+                let last = self.auxiliaries.len().saturating_sub(1);
+                let auxiliaries = self.auxiliaries.iter_mut().enumerate().fold(
+                    Scrollable::new(&mut self.aux_scroll).spacing(5),
+                    |rows, (id, aux)| rows.push(aux.view(id, id == last)),
+                );
+
+                let src_sheet_text = &self.src_sheet_text;
+                let selected_sheet = self
+                    .sheet_names
+                    .iter()
+                    .position(|name| name == src_sheet_text);
+                let sheets = self.sheet_names.iter().enumerate().fold(
+                    Scrollable::new(&mut self.sheet_scroll).spacing(5),
+                    |sheets, (index, name)| {
+                        sheets.push(Radio::new(
+                            index,
+                            name,
+                            selected_sheet,
+                            Message::SrcSheetSelected,
+                        ))
+                    },
+                );
+
+                let mut header_rows: Vec<Row<_>> = Vec::new();
+                for (i, ((column, label), state)) in self
+                    .header_labels
+                    .iter()
+                    .zip(self.header_buttons.iter_mut())
+                    .enumerate()
+                {
+                    let row = match i % HEADER_COLUMNS {
+                        0 => Row::new().spacing(5),
+                        _ => header_rows.pop().expect("row was pushed before"),
+                    };
+                    header_rows.push(
+                        row.push(
+                            Button::new(
+                                state,
+                                Text::new(format!("{}: {}", column.to_column_name(), label))
+                                    .size(14),
+                            )
+                            .on_press(Message::HeaderColumnPressed(*column))
+                            .width(Length::Units(100)),
+                        ),
+                    );
+                }
+                let header = header_rows.into_iter().fold(
+                    Scrollable::new(&mut self.header_scroll).spacing(5),
+                    Scrollable::push,
+                );
 
                 column
                     .push(
@@ -331,35 +839,78 @@ impl Application for Main {
                                 .on_submit(Message::GeneratePressed)
                                 .padding(5),
                             )
-                            .push(Text::new("Sheet"))
+                            .push(Text::new("Column"))
                             .push(
                                 TextInput::new(
-                                    &mut self.src_sheet_input,
-                                    "sheet name",
-                                    &self.src_sheet_text,
-                                    Message::SrcSheetInputChanged,
+                                    &mut self.src_column_input,
+                                    "P:U",
+                                    &self.src_column_text,
+                                    Message::SrcColumnInputChanged,
                                 )
-                                .on_submit(Message::GeneratePressed)
                                 .padding(5)
-                                .width(Length::Units(120)),
+                                .width(Length::Units(80)),
                             )
-                            .push(Text::new("Column"))
+                            .push(Text::new("Header"))
                             .push(
                                 TextInput::new(
-                                    &mut self.src_column_input,
-                                    "A",
-                                    &self.src_column_text,
-                                    Message::SrcColumnInputChanged,
+                                    &mut self.header_row_input,
+                                    "auto",
+                                    &self.header_row_text,
+                                    Message::HeaderRowInputChanged,
                                 )
                                 .padding(5)
-                                .width(Length::Units(30)),
+                                .width(Length::Units(40)),
                             ),
                     )
                     .push(
-                        Row::with_children(auxiliaries)
+                        Row::new()
                             .align_items(Align::Start)
                             .padding(20)
-                            .spacing(10),
+                            .spacing(20)
+                            .push(
+                                Column::new()
+                                    .spacing(10)
+                                    .width(Length::Units(200))
+                                    .push(Text::new("Sheet"))
+                                    .push(sheets.height(Length::Units(100))),
+                            )
+                            .push(
+                                Column::new()
+                                    .spacing(10)
+                                    .push(Text::new(self.header_text.clone()))
+                                    .push(header.height(Length::Units(100))),
+                            ),
+                    )
+                    .push(
+                        Row::new()
+                            .align_items(Align::Start)
+                            .padding(20)
+                            .spacing(20)
+                            .push(
+                                Column::new()
+                                    .spacing(10)
+                                    .width(Length::Units(340))
+                                    .push(Text::new("Columns"))
+                                    .push(base_columns.height(Length::Units(100))),
+                            )
+                            .push(
+                                Column::new()
+                                    .spacing(10)
+                                    .push(
+                                        Row::new()
+                                            .align_items(Align::Center)
+                                            .spacing(20)
+                                            .push(Text::new("Additional Columns"))
+                                            .push(
+                                                Button::new(
+                                                    &mut self.aux_add_button,
+                                                    Text::new("Add").size(14),
+                                                )
+                                                .on_press(Message::AuxAddPressed),
+                                            ),
+                                    )
+                                    .push(auxiliaries.height(Length::Units(100))),
+                            ),
                     )
                     .push(
                         Row::new()
@@ -370,19 +921,293 @@ impl Application for Main {
                             .push(
                                 TextInput::new(
                                     &mut self.dest_path_input,
-                                    "path to csv file",
+                                    "path to xlsx or csv file",
                                     &self.dest_path_text,
                                     Message::DestPathInputChanged,
                                 )
                                 .padding(5),
+                            )
+                            .push(Checkbox::new(
+                                self.preferences.show_price,
+                                "Price in column",
+                                Message::ShowPriceToggled,
+                            ))
+                            .push(
+                                TextInput::new(
+                                    &mut self.price_column_input,
+                                    "XX",
+                                    &self.preferences.price_column,
+                                    Message::PriceColumnInputChanged,
+                                )
+                                .padding(5)
+                                .width(Length::Units(40)),
+                            )
+                            .push(Checkbox::new(
+                                !self.preferences.hide_summary,
+                                "Summary",
+                                Message::ShowSummaryToggled,
+                            )),
+                    )
+                    .push(
+                        Row::new()
+                            .align_items(Align::Center)
+                            .padding(20)
+                            .spacing(20)
+                            .push(Text::new("Groups"))
+                            .push(Radio::new(
+                                GroupSplit::Off,
+                                "One list",
+                                Some(self.preferences.split_groups),
+                                Message::SplitGroupsChanged,
+                            ))
+                            .push(Radio::new(
+                                GroupSplit::Sheets,
+                                "One sheet per group",
+                                Some(self.preferences.split_groups),
+                                Message::SplitGroupsChanged,
+                            ))
+                            .push(Radio::new(
+                                GroupSplit::Files,
+                                "One file per group (destination is a directory)",
+                                Some(self.preferences.split_groups),
+                                Message::SplitGroupsChanged,
+                            )),
+                    )
+                    .push(
+                        Row::new()
+                            .align_items(Align::Center)
+                            .padding(20)
+                            .spacing(20)
+                            .push(Text::new("Print"))
+                            .push(Radio::new(
+                                Orientation::Portrait,
+                                "Portrait",
+                                Some(page.orientation),
+                                Message::OrientationChanged,
+                            ))
+                            .push(Radio::new(
+                                Orientation::Landscape,
+                                "Landscape",
+                                Some(page.orientation),
+                                Message::OrientationChanged,
+                            ))
+                            .push(Checkbox::new(
+                                page.fit_to_width,
+                                "Fit to width",
+                                Message::FitToWidthToggled,
+                            ))
+                            .push(Checkbox::new(
+                                page.repeat_header,
+                                "Repeat header",
+                                Message::RepeatHeaderToggled,
+                            ))
+                            .push(Checkbox::new(
+                                page.header_footer,
+                                "Header and footer",
+                                Message::HeaderFooterToggled,
+                            ))
+                            .push(Checkbox::new(
+                                page.gridlines,
+                                "Gridlines",
+                                Message::GridlinesToggled,
+                            ))
+                            .push(Checkbox::new(
+                                page.freeze_header,
+                                "Freeze header",
+                                Message::FreezeHeaderToggled,
+                            )),
+                    )
+                    .push(
+                        Row::new()
+                            .align_items(Align::Center)
+                            .padding(20)
+                            .spacing(10)
+                            .push(Radio::new(
+                                ListKind::Contacts,
+                                "Contacts",
+                                Some(list_kind),
+                                Message::ListKindChanged,
+                            ))
+                            .push(Radio::new(
+                                ListKind::Attendance,
+                                "Attendance from",
+                                Some(list_kind),
+                                Message::ListKindChanged,
+                            ))
+                            .push(
+                                TextInput::new(
+                                    &mut self.course_start_input,
+                                    "dd.mm.yyyy",
+                                    &self.course_start_text,
+                                    Message::CourseStartInputChanged,
+                                )
+                                .padding(5)
+                                .width(Length::Units(100)),
+                            )
+                            .push(Text::new("on"))
+                            .push(
+                                Button::new(&mut self.weekday_button, Text::new(weekday).size(14))
+                                    .on_press(Message::WeekdayPressed),
+                            )
+                            .push(
+                                TextInput::new(
+                                    &mut self.lessons_input,
+                                    "10",
+                                    &self.lessons_text,
+                                    Message::LessonsInputChanged,
+                                )
+                                .padding(5)
+                                .width(Length::Units(40)),
+                            )
+                            .push(Text::new("lessons, holidays"))
+                            .push(
+                                TextInput::new(
+                                    &mut self.holidays_input,
+                                    "24.12.2020, 01.04.2021-11.04.2021",
+                                    &self.holidays_text,
+                                    Message::HolidaysInputChanged,
+                                )
+                                .padding(5),
                             ),
                     )
+                    .push(
+                        Row::new()
+                            .align_items(Align::Center)
+                            .padding(20)
+                            .spacing(20)
+                            .push(Checkbox::new(
+                                self.preferences.skip_invalid_rows,
+                                "Leave out invalid rows",
+                                Message::SkipInvalidRowsToggled,
+                            ))
+                            .push(Checkbox::new(
+                                self.preferences.exclude_invalid_emails,
+                                "Leave out invalid email addresses",
+                                Message::ExcludeInvalidEmailsToggled,
+                            ))
+                            .push(Text::new("Sort by"))
+                            .push(
+                                Button::new(&mut self.sort_button, Text::new(sort_label).size(14))
+                                    .on_press(Message::SortKeyPressed),
+                            )
+                            .push(Checkbox::new(
+                                self.preferences.sort.descending,
+                                "Descending",
+                                Message::SortDescendingToggled,
+                            )),
+                    )
                     .push(Space::with_height(Length::Fill))
                     .push(
                         Button::new(&mut self.generate_button, Text::new("Generate"))
                             .on_press(Message::GeneratePressed),
                     )
             }
+            Preview => {
+                let (list, options) = match &self.preview {
+                    Some(preview) => preview,
+                    None => return column.into(),
+                };
+                let table = preview_table(&list.entries, options);
+                let warnings = list
+                    .entries
+                    .iter()
+                    .filter(|entry| list.issues_of(entry).next().is_some())
+                    .count();
+                let warning_color = Color::from_rgb(0.8, 0.1, 0.1);
+
+                let header = table
+                    .columns
+                    .iter()
+                    .fold(Row::new().spacing(5), |row, col| {
+                        row.push(
+                            Text::new(col.title.clone())
+                                .size(14)
+                                .width(Length::Units(col.width as u16 * 5 + 20)),
+                        )
+                    });
+                // rows which were left out are listed first
+                let skipped = list.issues.iter().filter(|issue| issue.is_error()).fold(
+                    Scrollable::new(&mut self.preview_scroll)
+                        .spacing(2)
+                        .height(Length::Fill),
+                    |rows, issue| {
+                        rows.push(
+                            Text::new(format!("{} (left out)", issue))
+                                .size(14)
+                                .color(warning_color),
+                        )
+                    },
+                );
+                let rows = table.rows.iter().zip(list.entries.iter()).fold(
+                    skipped,
+                    |rows, (cells, entry)| {
+                        let warnings: Vec<String> = list
+                            .issues_of(entry)
+                            .map(|issue| issue.kind.to_string())
+                            .collect();
+                        let color = if warnings.is_empty() {
+                            Color::BLACK
+                        } else {
+                            warning_color
+                        };
+
+                        rows.push(
+                            cells
+                                .iter()
+                                .zip(table.columns.iter())
+                                .fold(Row::new().spacing(5), |row, (cell, col)| {
+                                    row.push(
+                                        Text::new(cell.to_string())
+                                            .size(14)
+                                            .color(color)
+                                            .width(Length::Units(col.width as u16 * 5 + 20)),
+                                    )
+                                })
+                                .push(Text::new(warnings.join(", ")).size(14).color(color)),
+                        )
+                    },
+                );
+                // totals and summary follow the participants
+                let rows = table
+                    .all_rows()
+                    .skip(table.rows.len())
+                    .fold(rows, |rows, (_, cells)| {
+                    rows.push(cells.iter().zip(table.columns.iter()).fold(
+                        Row::new().spacing(5),
+                        |row, (cell, col)| {
+                            row.push(
+                                Text::new(cell.to_string())
+                                    .size(14)
+                                    .width(Length::Units(col.width as u16 * 5 + 20)),
+                            )
+                        },
+                    ))
+                });
+
+                column
+                    .push(Text::new(format!(
+                        "Found {} participants in header row {} ({}), {} with warnings, {} rows left out",
+                        list.entries.len(),
+                        list.header_row,
+                        list.header,
+                        warnings,
+                        list.skipped_rows()
+                    )))
+                    .push(header)
+                    .push(rows)
+                    .push(
+                        Row::new()
+                            .spacing(20)
+                            .push(
+                                Button::new(&mut self.back_button, Text::new("Cancel"))
+                                    .on_press(Message::BackPressed),
+                            )
+                            .push(
+                                Button::new(&mut self.confirm_button, Text::new("Write"))
+                                    .on_press(Message::ConfirmPressed),
+                            ),
+                    )
+            }
             Error => column
                 .push(Row::new().push(Text::new(self.error_text.clone()).size(18)))
                 .push(Space::with_height(Length::Fill))
@@ -0,0 +1,86 @@
+//! Comparison of text the way German users expect it
+//!
+//! Follows DIN 5007 variant 1 as used in dictionaries: umlauts sort like their
+//! base letter and `ß` like `ss`. Case and accents only decide between words
+//! which are equal otherwise.
+
+use std::cmp::Ordering;
+
+/// Text which compares by value the way `compare` orders the original text
+pub fn sort_key(s: &str) -> String {
+    let mut key = String::with_capacity(s.len());
+
+    for c in s.chars().flat_map(char::to_lowercase) {
+        match c {
+            'ä' | 'à' | 'á' | 'â' | 'å' => key.push('a'),
+            'ç' => key.push('c'),
+            'è' | 'é' | 'ê' | 'ë' => key.push('e'),
+            'ì' | 'í' | 'î' | 'ï' => key.push('i'),
+            'ñ' => key.push('n'),
+            'ö' | 'ò' | 'ó' | 'ô' | 'ø' => key.push('o'),
+            'ü' | 'ù' | 'ú' | 'û' => key.push('u'),
+            'ß' => key.push_str("ss"),
+            c => key.push(c),
+        }
+    }
+
+    key
+}
+
+/// Compares two texts by their `sort_key` and then by their characters
+pub fn compare(a: &str, b: &str) -> Ordering {
+    sort_key(a).cmp(&sort_key(b)).then_with(|| a.cmp(b))
+}
+
+/// Text ordered by `compare`, e.g. as key of a `BTreeMap`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collated<S>(pub S);
+
+impl<S: AsRef<str> + Eq> Ord for Collated<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare(self.0.as_ref(), other.0.as_ref())
+    }
+}
+
+impl<S: AsRef<str> + Eq> PartialOrd for Collated<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn german_order() {
+        let mut names = vec![
+            "Zander", "Öztürk", "Ofen", "Müller", "Mueller", "Muller", "anna", "Anna", "Straße",
+            "Strasse", "Strauss", "Äpfel", "Apfel",
+        ];
+        names.sort_by(|a, b| compare(a, b));
+
+        assert_eq!(
+            names,
+            vec![
+                "Anna", "anna", "Apfel", "Äpfel", "Mueller", "Muller", "Müller", "Ofen", "Öztürk",
+                "Strasse", "Straße", "Strauss", "Zander",
+            ]
+        );
+    }
+
+    #[test]
+    fn collated_keys() {
+        let groups: BTreeSet<Collated<&str>> = ["Zwerge", "ölkurs", "Otter", "aqua"]
+            .iter()
+            .copied()
+            .map(Collated)
+            .collect();
+
+        assert_eq!(
+            groups.into_iter().map(|group| group.0).collect::<Vec<_>>(),
+            vec!["aqua", "ölkurs", "Otter", "Zwerge"]
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Sign-in sheets with a column for every lesson of a course
+
+use super::{CourseEntry, CourseListError, CourseListOptions};
+use crate::{
+    attendance,
+    output::{Cell, Table, TableColumn},
+};
+use chrono::NaiveDate;
+
+/// Header of the column holding the customer id
+pub const ID_HEADER: &str = "Kundennummer";
+
+/// Header of the column holding the name
+pub const NAME_HEADER: &str = "Name";
+
+/// Format of the dates in the header of the lesson columns
+pub const DATE_FORMAT: &str = "%d.%m.%y";
+
+/// Dates of the lessons configured in `options`
+pub fn lesson_dates(options: &CourseListOptions) -> Result<Vec<NaiveDate>, CourseListError> {
+    let attendance = &options.attendance;
+    let start = attendance
+        .start
+        .ok_or(CourseListError::MissingCourseStart)?;
+
+    Ok(attendance::lesson_dates(
+        start,
+        attendance.weekday,
+        attendance.lessons,
+        &attendance.holidays,
+    ))
+}
+
+/// Converts a sorted list into a sign-in sheet with empty cells for every lesson
+pub fn attendance_table(list: &[CourseEntry], name: Option<String>, dates: &[NaiveDate]) -> Table {
+    let columns = vec![
+        TableColumn::new(ID_HEADER, 5.).centered(),
+        TableColumn::new(NAME_HEADER, 25.),
+    ]
+    .into_iter()
+    .chain(
+        dates
+            .iter()
+            .map(|date| TableColumn::new(&date.format(DATE_FORMAT).to_string(), 8.).centered()),
+    )
+    .collect();
+
+    let rows = list
+        .iter()
+        .map(|entry| {
+            vec![
+                Cell::Number(entry.id().into()),
+                Cell::Text(entry.name().to_string()),
+            ]
+            .into_iter()
+            .chain(dates.iter().map(|_| Cell::Text(String::new())))
+            .collect()
+        })
+        .collect();
+
+    Table {
+        name,
+        title: single_group(list),
+        columns,
+        rows,
+        totals: Vec::new(),
+        summary: Vec::new(),
+    }
+}
+
+/// The group of all entries if they share one
+pub fn single_group(list: &[CourseEntry]) -> Option<String> {
+    let first = list.first()?.group();
+
+    list.iter()
+        .all(|entry| entry.group() == first)
+        .then(|| first.to_string())
+}
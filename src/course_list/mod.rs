@@ -0,0 +1,1840 @@
+//! Course lists read from the booking sheet and written as contact or
+//! sign-in sheets
+
+use self::attendance::{attendance_table, lesson_dates, single_group};
+use self::validation::{Issue, IssueKind};
+use crate::{
+    collation::{self, Collated},
+    email::parse_addresses,
+    output::{
+        unique_file_name, unique_sheet_name, writer_for, Cell, OutputError, Table, TableColumn,
+    },
+    phone::parse_numbers,
+    preferences::{
+        used_auxiliary_columns, AttendanceOptions, AuxiliaryColumn, AuxiliaryFormat, BaseColumn,
+        BaseColumns, BaseField, ColumnMapping, CsvOptions, GroupSplit, ListKind, OutputFormat,
+        PageSetup, PhoneFormat, Preferences, SortKey, Sorting,
+    },
+    reference::{column_index, ColumnRange, ReferenceError},
+    workbook::{CellError, CellValue, WorkbookError, WorkbookManager},
+    ColumnName,
+};
+use calamine::{DataType, Range, Reader, Sheets};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    convert::TryFrom,
+    fmt,
+    fs::create_dir_all,
+    io,
+    num::TryFromIntError,
+    path::Path,
+};
+use thiserror::Error;
+
+pub mod attendance;
+pub mod statistics;
+pub mod validation;
+
+#[derive(Debug, Error)]
+pub enum CourseListError {
+    #[error("No Worksheet loaded")]
+    NoReader,
+
+    #[error("Invalid price column: {0}")]
+    InvalidPriceColumn(#[source] ReferenceError),
+
+    #[error("Invalid columns: {0}")]
+    InvalidColumns(#[source] ReferenceError),
+
+    #[error("Invalid column `{column}` of additional column `{name}`: {source}")]
+    InvalidAuxiliary {
+        name: String,
+        column: String,
+        #[source]
+        source: ReferenceError,
+    },
+
+    #[error("Sheet has no header row {0}")]
+    NoHeaderRow(usize),
+
+    #[error("Could not find {} in header row {row}", quoted_labels(.headers))]
+    MissingHeader { headers: Vec<String>, row: usize },
+
+    #[error("Could not find a row containing all header labels")]
+    HeaderNotFound,
+
+    #[error(
+        "Group column {} is beyond the last used column {} of the header in row {row}",
+        .column.to_column_name(),
+        .last.to_column_name()
+    )]
+    ColumnOutOfRange {
+        column: usize,
+        last: usize,
+        row: usize,
+    },
+
+    #[error("Workbook Error: {0}")]
+    WorkbookError(
+        #[from]
+        #[source]
+        WorkbookError,
+    ),
+
+    #[error("Workbook Error: {0}")]
+    ConversionError(
+        #[from]
+        #[source]
+        TryFromIntError,
+    ),
+
+    #[error(
+        "Could not read {} rows, the first problem is: {}",
+        .0.len(),
+        .0.first().map(ToString::to_string).unwrap_or_default()
+    )]
+    InvalidRows(Vec<Issue>),
+
+    #[error("No first day of the course given for the attendance list")]
+    MissingCourseStart,
+
+    #[error("Output format does not support multiple sheets, write one file per group instead")]
+    SheetsNotSupported,
+
+    #[error("Could not create directory: {0}")]
+    CreateDirectory(#[source] io::Error),
+
+    #[error("{0}")]
+    Output(
+        #[from]
+        #[source]
+        OutputError,
+    ),
+}
+
+/// Lists labels like column `Telefon` or columns `Telefon`, `E-Mail`
+fn quoted_labels(labels: &[String]) -> String {
+    let quoted: Vec<String> = labels.iter().map(|label| format!("`{}`", label)).collect();
+
+    match quoted.len() {
+        1 => format!("column {}", quoted[0]),
+        _ => format!("columns {}", quoted.join(", ")),
+    }
+}
+
+/// Number of a customer in the booking sheet
+pub type CustomerId = i32;
+
+#[derive(Debug)]
+pub struct CourseEntry {
+    /// Row in the source sheet starting at 1
+    pub(crate) row: usize,
+    pub(crate) id: CustomerId,
+    pub(crate) group: String,
+    /// Columns of the source sheet the group was found in
+    pub(crate) columns: Vec<usize>,
+    pub(crate) name: String,
+    pub(crate) telephone: Vec<String>,
+    pub(crate) email: Vec<String>,
+    pub(crate) price: Option<f64>,
+    pub(crate) auxiliaries: Vec<Cell>,
+}
+
+impl CourseEntry {
+    pub fn id(&self) -> CustomerId {
+        self.id
+    }
+
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Last word of the name, for names written like `Anna Huber`
+    fn surname(&self) -> &str {
+        self.name.split_whitespace().last().unwrap_or_default()
+    }
+
+    /// Compares in ascending order by `key` and then by customer id
+    fn compare(&self, other: &Self, key: SortKey) -> Ordering {
+        let name = || collation::compare(&self.name, &other.name);
+
+        match key {
+            SortKey::Name => name(),
+            SortKey::GroupName => collation::compare(&self.group, &other.group).then_with(name),
+            SortKey::Surname => collation::compare(self.surname(), other.surname()).then_with(name),
+            SortKey::Id => Ordering::Equal,
+            SortKey::Auxiliary(index) => {
+                compare_cells(self.auxiliaries.get(index), other.auxiliaries.get(index))
+            }
+        }
+        .then(self.id.cmp(&other.id))
+    }
+}
+
+/// Orders numbers before text, numbers by value and text by German collation
+fn compare_cells(a: Option<&Cell>, b: Option<&Cell>) -> Ordering {
+    match (a, b) {
+        (Some(Cell::Number(a)), Some(Cell::Number(b))) => {
+            a.partial_cmp(b).unwrap_or(Ordering::Equal)
+        }
+        (Some(Cell::Number(_)), Some(Cell::Text(_))) => Ordering::Less,
+        (Some(Cell::Text(_)), Some(Cell::Number(_))) => Ordering::Greater,
+        (Some(Cell::Text(a)), Some(Cell::Text(b))) => collation::compare(a, b),
+        (a, b) => a.is_some().cmp(&b.is_some()),
+    }
+}
+
+/// Sorts the list as configured
+pub fn sort_course_list(list: &mut [CourseEntry], sorting: Sorting) {
+    list.sort_by(|a, b| {
+        let order = a.compare(b, sorting.key);
+        match sorting.descending {
+            true => order.reverse(),
+            false => order,
+        }
+    });
+}
+
+#[derive(Debug, Default)]
+pub struct CourseListOptions {
+    pub show_price: bool,
+    /// Column letters of the price, only read if it is shown
+    pub price_column: String,
+    pub auxiliaries: Vec<AuxiliaryColumn>,
+    pub columns: ColumnMapping,
+    /// Base columns of the written list in their order
+    pub base_columns: BaseColumns,
+    pub sort: Sorting,
+    pub format: OutputFormat,
+    pub csv: CsvOptions,
+    pub page: PageSetup,
+    pub split_groups: GroupSplit,
+    /// Leave out rows which can not be read instead of failing
+    pub skip_invalid_rows: bool,
+    pub phone_format: PhoneFormat,
+    /// Leave out invalid email addresses, e.g. for mailing lists
+    pub exclude_invalid_emails: bool,
+    /// Leave out the summary below the list
+    pub hide_summary: bool,
+    pub list_kind: ListKind,
+    pub attendance: AttendanceOptions,
+}
+
+impl From<&Preferences> for CourseListOptions {
+    fn from(prefs: &Preferences) -> Self {
+        let (auxiliaries, sort) = used_auxiliary_columns(&prefs.auxiliary_columns, prefs.sort);
+
+        Self {
+            show_price: prefs.show_price,
+            price_column: prefs.price_column.clone(),
+            auxiliaries,
+            columns: prefs.columns.clone(),
+            base_columns: prefs.base_columns.clone(),
+            sort,
+            format: prefs.dest_format,
+            csv: prefs.csv.clone(),
+            page: prefs.page,
+            split_groups: prefs.split_groups,
+            skip_invalid_rows: prefs.skip_invalid_rows,
+            phone_format: prefs.phone_format,
+            exclude_invalid_emails: prefs.exclude_invalid_emails,
+            hide_summary: prefs.hide_summary,
+            list_kind: prefs.list_kind,
+            attendance: prefs.attendance.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParsedCourseList {
+    /// Row of the header in the sheet starting at 1
+    pub header_row: usize,
+    /// Columns the base fields were found in
+    pub header: HeaderIndices,
+    pub entries: Vec<CourseEntry>,
+    /// Problems of all read rows including the ones which were left out
+    pub issues: Vec<Issue>,
+}
+
+impl ParsedCourseList {
+    /// Problems found in the row of `entry`
+    pub fn issues_of<'a>(&'a self, entry: &'a CourseEntry) -> impl Iterator<Item = &'a Issue> {
+        self.issues
+            .iter()
+            .filter(move |issue| issue.row == entry.row)
+    }
+
+    /// Number of rows which could not be read
+    pub fn skipped_rows(&self) -> usize {
+        self.issues.iter().filter(|issue| issue.is_error()).count()
+    }
+}
+
+/// Value of cells outside of the used range of a sheet
+static EMPTY: DataType = DataType::Empty;
+
+/// Row of the source sheet with access to its cells by column of the sheet
+struct SheetRow<'a> {
+    /// Row in the sheet starting at 1
+    row: usize,
+    /// Column of the first cell in `cells`
+    first_column: usize,
+    cells: &'a [DataType],
+}
+
+impl<'a> SheetRow<'a> {
+    /// Cell in `column` which is empty outside of the used range
+    fn get(&self, column: usize) -> &'a DataType {
+        column
+            .checked_sub(self.first_column)
+            .and_then(|column| self.cells.get(column))
+            .unwrap_or(&EMPTY)
+    }
+}
+
+/// Indices of the base columns as found in the header row
+#[derive(Debug, PartialEq)]
+pub struct HeaderIndices {
+    id: usize,
+    name: usize,
+    telephone: usize,
+    email: usize,
+}
+
+impl fmt::Display for HeaderIndices {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "id in {}, name in {}, telephone in {}, email in {}",
+            self.id.to_column_name(),
+            self.name.to_column_name(),
+            self.telephone.to_column_name(),
+            self.email.to_column_name()
+        )
+    }
+}
+
+impl HeaderIndices {
+    /// Finds the labels of `mapping` in `header` which starts at `first_column`
+    fn locate(
+        header: &[DataType],
+        first_column: usize,
+        mapping: &ColumnMapping,
+        row: usize,
+    ) -> Result<Self, CourseListError> {
+        let labels = [
+            &mapping.id,
+            &mapping.name,
+            &mapping.telephone,
+            &mapping.email,
+        ];
+        let columns: Vec<Option<usize>> = labels
+            .iter()
+            .map(|label| {
+                header
+                    .iter()
+                    .position(|cell| {
+                        cell.get_string()
+                            .is_some_and(|text| text.trim().eq_ignore_ascii_case(label.trim()))
+                    })
+                    .map(|position| first_column + position)
+            })
+            .collect();
+
+        match columns[..] {
+            [Some(id), Some(name), Some(telephone), Some(email)] => Ok(Self {
+                id,
+                name,
+                telephone,
+                email,
+            }),
+            _ => Err(CourseListError::MissingHeader {
+                headers: labels
+                    .iter()
+                    .zip(&columns)
+                    .filter(|(_, column)| column.is_none())
+                    .map(|(label, _)| label.to_string())
+                    .collect(),
+                row,
+            }),
+        }
+    }
+
+    /// Reads the telephone numbers of a data row in `format`
+    ///
+    /// Invalid numbers are kept as they were written and reported.
+    fn telephone(
+        &self,
+        data: &SheetRow,
+        format: PhoneFormat,
+        issues: &mut Vec<Issue>,
+    ) -> Vec<String> {
+        let row = data.row;
+        let numbers = parse_numbers(&read_text(
+            data.get(self.telephone),
+            CellValue::phone_text,
+            Issue::new(row, self.telephone, IssueKind::MissingTelephone),
+            issues,
+        ));
+
+        numbers
+            .into_iter()
+            .map(|number| match number {
+                Ok(number) => match format {
+                    PhoneFormat::National => number.national(),
+                    PhoneFormat::International => number.e164(),
+                },
+                Err((text, err)) => {
+                    issues.push(Issue::new(
+                        row,
+                        self.telephone,
+                        IssueKind::InvalidTelephone(text.clone(), err),
+                    ));
+                    text
+                }
+            })
+            .collect()
+    }
+
+    /// Reads the email addresses of a data row
+    ///
+    /// Invalid addresses are reported and only kept as they were written
+    /// unless `exclude_invalid` is set.
+    fn email(
+        &self,
+        data: &SheetRow,
+        exclude_invalid: bool,
+        issues: &mut Vec<Issue>,
+    ) -> Vec<String> {
+        let row = data.row;
+        let addresses = parse_addresses(&read_text(
+            data.get(self.email),
+            CellValue::text,
+            Issue::new(row, self.email, IssueKind::MissingEmail),
+            issues,
+        ));
+
+        addresses
+            .into_iter()
+            .filter_map(|address| match address {
+                Ok(address) => Some(address.to_string()),
+                Err((text, err)) => {
+                    issues.push(Issue::new(
+                        row,
+                        self.email,
+                        IssueKind::InvalidEmail(text.clone(), err),
+                    ));
+                    (!exclude_invalid).then_some(text)
+                }
+            })
+            .collect()
+    }
+}
+
+pub trait CourseList<R>
+where
+    R: Reader,
+{
+    fn sheets(&self) -> Option<&[String]>;
+    fn get_sheet(&mut self, name: &str) -> Option<Result<Range<DataType>, WorkbookError>>;
+
+    /// Reads the labels of the header row as pairs of column index and label
+    ///
+    /// Returns the row of the header starting at 1 as well.
+    fn read_header(
+        &mut self,
+        sheet: &str,
+        mapping: &ColumnMapping,
+    ) -> Result<(usize, Vec<(usize, String)>), CourseListError> {
+        let range = self.get_sheet(sheet).ok_or(CourseListError::NoReader)??;
+        let (first_row, header_index, _) = locate_header(&range, mapping)?;
+        let first_column = range.start().map_or(0, |(_, column)| column as usize);
+
+        let labels = range
+            .rows()
+            .nth(header_index)
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_, cell)| !cell.is_empty())
+            .map(|(i, cell)| (first_column + i, cell.to_string().trim().to_string()))
+            .collect();
+
+        Ok((first_row + header_index + 1, labels))
+    }
+
+    /// Reads the participants of all groups in `columns`
+    ///
+    /// `columns` is a single column, a range like `P:U` or a comma separated
+    /// list of both. Customers booked into the same group in several columns
+    /// are only listed once.
+    fn read_course_list(
+        &mut self,
+        sheet: &str,
+        columns: &str,
+        options: &CourseListOptions,
+    ) -> Result<ParsedCourseList, CourseListError> {
+        let columns = parse_group_columns(columns)?;
+        let aux_columns = auxiliary_columns(&options.auxiliaries)?;
+        let price_column = match options.show_price {
+            true => Some(
+                column_index(options.price_column.trim())
+                    .map_err(CourseListError::InvalidPriceColumn)?,
+            ),
+            false => None,
+        };
+
+        let range = self.get_sheet(sheet).ok_or(CourseListError::NoReader)??;
+        let (first_row, header_index, indices) = locate_header(&range, &options.columns)?;
+        let first_column = range.start().map_or(0, |(_, column)| column as usize);
+
+        // columns outside of the used range are empty, which is fine for
+        // some but not all group columns
+        let last = range.end().map_or(0, |(_, column)| column as usize);
+        if columns.iter().all(|&column| column > last) {
+            return Err(CourseListError::ColumnOutOfRange {
+                column: columns[0],
+                last,
+                row: first_row + header_index + 1,
+            });
+        }
+
+        let mut entries: Vec<CourseEntry> = Vec::new();
+        let mut known: HashMap<(CustomerId, String), usize> = HashMap::new();
+        let mut issues = Vec::new();
+
+        // skip header
+        for (i, cells) in range.rows().enumerate().skip(header_index + 1) {
+            let row = first_row + i + 1;
+            let data = SheetRow {
+                row,
+                first_column,
+                cells,
+            };
+
+            // filter out rows without an entry in these columns
+            let mut groups = Vec::new();
+            for &column in &columns {
+                let cell = data.get(column);
+                if cell.is_string() {
+                    if let Ok(group) = cell.text() {
+                        groups.push((column, group));
+                    }
+                }
+            }
+            if groups.is_empty() {
+                continue;
+            }
+
+            let id = match read_customer_id(data.get(indices.id)) {
+                Ok(id) => id,
+                Err(err) => {
+                    issues.push(Issue::new(row, indices.id, IssueKind::InvalidId(err)));
+                    continue;
+                }
+            };
+            let name = read_text(
+                data.get(indices.name),
+                CellValue::text,
+                Issue::new(row, indices.name, IssueKind::MissingName),
+                &mut issues,
+            );
+            let telephone = indices.telephone(&data, options.phone_format, &mut issues);
+            let email = indices.email(&data, options.exclude_invalid_emails, &mut issues);
+            let price = match price_column {
+                Some(column) => read_price(
+                    data.get(column),
+                    Issue::new(row, column, IssueKind::MissingPrice),
+                    &mut issues,
+                ),
+                None => None,
+            };
+            let auxiliaries = aux_columns
+                .iter()
+                .zip(options.auxiliaries.iter())
+                .map(|(&column, aux)| {
+                    read_auxiliary(
+                        data.get(column),
+                        aux.format,
+                        Issue::new(row, column, IssueKind::InvalidCell(CellError::Empty)),
+                        &mut issues,
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            for (column, group) in groups {
+                if let Some(&index) = known.get(&(id, group.clone())) {
+                    let entry: &mut CourseEntry = &mut entries[index];
+                    if !entry.columns.contains(&column) {
+                        entry.columns.push(column);
+                    }
+                    continue;
+                }
+
+                known.insert((id, group.clone()), entries.len());
+                entries.push(CourseEntry {
+                    row,
+                    id,
+                    group,
+                    columns: vec![column],
+                    name: name.clone(),
+                    telephone: telephone.clone(),
+                    email: email.clone(),
+                    price,
+                    auxiliaries: auxiliaries.clone(),
+                });
+            }
+        }
+
+        if !options.skip_invalid_rows && issues.iter().any(Issue::is_error) {
+            return Err(CourseListError::InvalidRows(
+                issues.into_iter().filter(Issue::is_error).collect(),
+            ));
+        }
+
+        Ok(ParsedCourseList {
+            header_row: first_row + header_index + 1,
+            header: indices,
+            entries,
+            issues,
+        })
+    }
+
+    fn write_course_list(
+        path: &str,
+        mut list: Vec<CourseEntry>,
+        options: &CourseListOptions,
+    ) -> Result<(), CourseListError> {
+        sort_course_list(&mut list, options.sort);
+
+        let show_columns = shows_columns(&list);
+        let dates = match options.list_kind {
+            ListKind::Contacts => None,
+            ListKind::Attendance => Some(lesson_dates(options)?),
+        };
+        let table = |entries: &[CourseEntry], name| match &dates {
+            Some(dates) => attendance_table(entries, name, dates),
+            None => course_list_table(entries, name, show_columns, options),
+        };
+
+        let format = options.format.resolve(path);
+        let writer = writer_for(format, &options.csv, options.page);
+
+        // attendance lists are written per group where the format allows it
+        let split = match (options.list_kind, options.split_groups) {
+            (ListKind::Attendance, GroupSplit::Off) if writer.supports_sheets() => {
+                GroupSplit::Sheets
+            }
+            (_, split) => split,
+        };
+
+        match split {
+            GroupSplit::Off => Ok(writer.write(Path::new(path), &[table(&list, None)])?),
+            GroupSplit::Sheets => {
+                if !writer.supports_sheets() {
+                    return Err(CourseListError::SheetsNotSupported);
+                }
+
+                let mut taken = Vec::new();
+                let tables: Vec<Table> = split_groups(list)
+                    .into_iter()
+                    .map(|(group, entries)| {
+                        let name = unique_sheet_name(&group, &mut taken);
+                        table(&entries, Some(name))
+                    })
+                    .collect();
+
+                Ok(writer.write(Path::new(path), &tables)?)
+            }
+            GroupSplit::Files => {
+                let dir = Path::new(path);
+                create_dir_all(dir).map_err(|err| CourseListError::CreateDirectory(err))?;
+
+                let mut taken = Vec::new();
+                for (group, entries) in split_groups(list) {
+                    let name = unique_file_name(&group, &mut taken);
+                    let path = dir.join(format!("{}.{}", name, format.extension()));
+
+                    // sign-in sheets are read back by the name of their sheet
+                    let sheet = dates
+                        .as_ref()
+                        .map(|_| unique_sheet_name(&group, &mut Vec::new()));
+
+                    writer.write(&path, &[table(&entries, sheet)])?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl CourseList<Sheets> for WorkbookManager {
+    fn sheets(&self) -> Option<&[String]> {
+        self.sheets()
+    }
+
+    fn get_sheet(&mut self, name: &str) -> Option<Result<Range<DataType>, WorkbookError>> {
+        self.get_sheet(name)
+    }
+}
+
+impl CourseEntry {
+    fn cell(&self, field: BaseField) -> Cell {
+        match field {
+            BaseField::Id => Cell::Number(self.id.into()),
+            BaseField::Group => Cell::Text(self.group.clone()),
+            BaseField::Name => Cell::Text(self.name.clone()),
+            BaseField::Telephone => Cell::Text(self.telephone.join("; ")),
+            BaseField::Email => Cell::Text(self.email.join("; ")),
+        }
+    }
+
+    fn cells(&self, base_columns: &[Option<&BaseColumn>], show_price: bool) -> Vec<Cell> {
+        let price = show_price.then(|| match self.price {
+            Some(price) => Cell::Currency(price),
+            None => Cell::Text(String::new()),
+        });
+
+        base_columns
+            .iter()
+            .map(|column| match column {
+                Some(column) => self.cell(column.field),
+                None => Cell::Text(
+                    self.columns
+                        .iter()
+                        .map(ColumnName::to_column_name)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+            })
+            .chain(price)
+            .chain(self.auxiliaries.iter().cloned())
+            .collect()
+    }
+}
+
+/// Reads a price, reporting `missing` if the cell is empty
+fn read_price(cell: &DataType, missing: Issue, issues: &mut Vec<Issue>) -> Option<f64> {
+    match cell.number() {
+        Ok(price) => Some(price),
+        Err(CellError::Empty) => {
+            issues.push(missing);
+            None
+        }
+        Err(err) => {
+            issues.push(Issue {
+                kind: IssueKind::InvalidCell(err),
+                ..missing
+            });
+            None
+        }
+    }
+}
+
+/// Reads a customer id written as whole number or text
+pub fn read_customer_id(cell: &DataType) -> Result<CustomerId, CellError> {
+    cell.int().and_then(|id| {
+        CustomerId::try_from(id).map_err(|_| CellError::NotAnInteger(id.to_string()))
+    })
+}
+
+/// Reads the text of a cell with `read`
+///
+/// Reports `missing` if the cell is empty and the error if it can not be read.
+/// In both cases the text is empty.
+fn read_text(
+    cell: &DataType,
+    read: fn(&DataType) -> Result<String, CellError>,
+    missing: Issue,
+    issues: &mut Vec<Issue>,
+) -> String {
+    match read(cell) {
+        Ok(text) => text,
+        Err(CellError::Empty) => {
+            issues.push(missing);
+            String::new()
+        }
+        Err(err) => {
+            issues.push(Issue {
+                kind: IssueKind::InvalidCell(err),
+                ..missing
+            });
+            String::new()
+        }
+    }
+}
+
+/// Reads the cell of an additional column in `format`
+///
+/// Empty cells are fine, cells which can not be read in `format` are reported
+/// at the position of `at` and written as text.
+fn read_auxiliary(
+    cell: &DataType,
+    format: AuxiliaryFormat,
+    at: Issue,
+    issues: &mut Vec<Issue>,
+) -> Cell {
+    let value = match format {
+        AuxiliaryFormat::Text => cell.text().map(Cell::Text),
+        AuxiliaryFormat::Number => cell.number().map(Cell::Number),
+        AuxiliaryFormat::Date => cell
+            .date()
+            .map(|date| Cell::Text(date.format("%d.%m.%Y").to_string())),
+    };
+
+    match value {
+        Ok(value) => value,
+        Err(CellError::Empty) => Cell::Text(String::new()),
+        Err(err) => {
+            issues.push(Issue {
+                kind: IssueKind::InvalidCell(err),
+                ..at
+            });
+            Cell::Text(cell.text().unwrap_or_default())
+        }
+    }
+}
+
+/// Finds the header row of `range`
+///
+/// Returns the first row of the range, the index of the header row in the
+/// range and the columns of the base fields.
+fn locate_header(
+    range: &Range<DataType>,
+    mapping: &ColumnMapping,
+) -> Result<(usize, usize, HeaderIndices), CourseListError> {
+    // the range starts at the first used cell of the sheet
+    let (first_row, first_column) = range
+        .start()
+        .map_or((0, 0), |(row, column)| (row as usize, column as usize));
+
+    let (header_index, indices) = match mapping.header_row {
+        Some(header_row) => {
+            let header_index = header_row
+                .checked_sub(first_row + 1)
+                .ok_or(CourseListError::NoHeaderRow(header_row))?;
+            let header = range
+                .rows()
+                .nth(header_index)
+                .ok_or(CourseListError::NoHeaderRow(header_row))?;
+
+            (
+                header_index,
+                HeaderIndices::locate(header, first_column, mapping, header_row)?,
+            )
+        }
+        None => detect_header(range, first_row, first_column, mapping)?,
+    };
+
+    Ok((first_row, header_index, indices))
+}
+
+/// Finds the first row of `range` containing all labels of `mapping`
+///
+/// Returns the index of the row in the range and the columns of the base
+/// fields. If there is none, the labels missing in the row containing most of
+/// them are reported.
+fn detect_header(
+    range: &Range<DataType>,
+    first_row: usize,
+    first_column: usize,
+    mapping: &ColumnMapping,
+) -> Result<(usize, HeaderIndices), CourseListError> {
+    let mut best: Option<(Vec<String>, usize)> = None;
+
+    for (i, cells) in range.rows().enumerate() {
+        match HeaderIndices::locate(cells, first_column, mapping, first_row + i + 1) {
+            Ok(indices) => return Ok((i, indices)),
+            // rows without any of the four labels are no candidates
+            Err(CourseListError::MissingHeader { headers, row }) if headers.len() < 4 => {
+                if best
+                    .as_ref()
+                    .is_none_or(|(missing, _)| headers.len() < missing.len())
+                {
+                    best = Some((headers, row));
+                }
+            }
+            Err(CourseListError::MissingHeader { .. }) => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Err(match best {
+        Some((headers, row)) => CourseListError::MissingHeader { headers, row },
+        None => CourseListError::HeaderNotFound,
+    })
+}
+
+/// Parses the columns of additional columns keeping their order
+fn auxiliary_columns(auxiliaries: &[AuxiliaryColumn]) -> Result<Vec<usize>, CourseListError> {
+    auxiliaries
+        .iter()
+        .map(|aux| {
+            column_index(aux.column.trim()).map_err(|source| CourseListError::InvalidAuxiliary {
+                name: aux.name.clone(),
+                column: aux.column.clone(),
+                source,
+            })
+        })
+        .collect()
+}
+
+/// Parses a list of columns and column ranges like `P:U, W` into indices
+fn parse_group_columns(spec: &str) -> Result<Vec<usize>, CourseListError> {
+    let mut columns = Vec::new();
+
+    for part in spec.split(',') {
+        let range: ColumnRange = part
+            .trim()
+            .parse()
+            .map_err(|err| CourseListError::InvalidColumns(err))?;
+
+        for column in range.columns() {
+            if !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+    }
+
+    Ok(columns)
+}
+
+/// Whether the source columns are written, only if there is more than one
+fn shows_columns(list: &[CourseEntry]) -> bool {
+    list.iter()
+        .flat_map(|entry| entry.columns.iter())
+        .collect::<BTreeSet<_>>()
+        .len()
+        > 1
+}
+
+/// Converts a sorted list into the table shown before writing it
+pub fn preview_table(list: &[CourseEntry], options: &CourseListOptions) -> Table {
+    match (options.list_kind, lesson_dates(options)) {
+        (ListKind::Attendance, Ok(dates)) => attendance_table(list, None, &dates),
+        _ => course_list_table(list, None, shows_columns(list), options),
+    }
+}
+
+/// Partitions a sorted list by the group of the entries keeping their order
+fn split_groups(list: Vec<CourseEntry>) -> Vec<(String, Vec<CourseEntry>)> {
+    let mut groups: BTreeMap<Collated<String>, Vec<CourseEntry>> = BTreeMap::new();
+
+    for entry in list {
+        groups
+            .entry(Collated(entry.group.clone()))
+            .or_default()
+            .push(entry);
+    }
+
+    groups
+        .into_iter()
+        .map(|(group, entries)| (group.0, entries))
+        .collect()
+}
+
+/// Converts a sorted list into the table written by all output formats
+fn course_list_table(
+    list: &[CourseEntry],
+    name: Option<String>,
+    show_columns: bool,
+    options: &CourseListOptions,
+) -> Table {
+    // `None` is the column of the group columns, it follows the group
+    let mut base_columns: Vec<Option<&BaseColumn>> =
+        options.base_columns.iter().map(Some).collect();
+    if show_columns {
+        let index = base_columns
+            .iter()
+            .position(|column| matches!(column, Some(column) if column.field == BaseField::Group))
+            .map_or(base_columns.len(), |index| index + 1);
+        base_columns.insert(index, None);
+    }
+
+    let columns: Vec<TableColumn> = base_columns
+        .iter()
+        .map(|column| match column {
+            Some(column) => match column.field {
+                BaseField::Id => TableColumn::new(&column.label, 5.).centered(),
+                BaseField::Group | BaseField::Email => TableColumn::new(&column.label, 30.),
+                BaseField::Name => TableColumn::new(&column.label, 20.),
+                BaseField::Telephone => TableColumn::new(&column.label, 15.),
+            },
+            None => TableColumn::new("Spalte", 8.),
+        })
+        .chain(options.show_price.then(|| TableColumn::new("Preis", 10.)))
+        .chain(
+            options
+                .auxiliaries
+                .iter()
+                .map(|aux| TableColumn::new(&aux.name, 30.)),
+        )
+        .collect();
+
+    let field_column = |field| {
+        base_columns
+            .iter()
+            .position(|column| matches!(column, Some(column) if column.field == field))
+    };
+    let group = field_column(BaseField::Group);
+
+    let totals = match options.show_price {
+        true => price_totals(list, group, base_columns.len(), columns.len()),
+        false => Vec::new(),
+    };
+    // the summary needs a column for its labels and one for the counts
+    let summary = match options.hide_summary || columns.len() < 2 {
+        true => Vec::new(),
+        false => summary(
+            list,
+            group,
+            field_column(BaseField::Id).or_else(|| field_column(BaseField::Name)),
+            field_column(BaseField::Telephone),
+            field_column(BaseField::Email),
+            columns.len(),
+        ),
+    };
+
+    Table {
+        name,
+        // lists of a single group are titled by it
+        title: single_group(list),
+        columns,
+        rows: list
+            .iter()
+            .map(|entry| entry.cells(&base_columns, options.show_price))
+            .collect(),
+        totals,
+        summary,
+    }
+}
+
+/// Cells of column `column` in the `rows` rows below the header like `B2:B20`
+fn rows_range(column: usize, rows: usize) -> String {
+    format!("{0}2:{0}{1}", column.to_column_name(), rows + 1)
+}
+
+/// Criterion of `COUNTIF` and `SUMIF` matching exactly `text`
+fn criterion(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '~' | '*' | '?' => escaped.push('~'),
+            '"' => escaped.push('"'),
+            _ => {}
+        }
+        escaped.push(c);
+    }
+
+    format!("\"={}\"", escaped)
+}
+
+/// `value` as formula if there are rows the formula refers to
+fn formula(list: &[CourseEntry], formula: impl FnOnce() -> String, value: Cell) -> Cell {
+    match list.is_empty() {
+        true => value,
+        false => Cell::Formula(formula(), Box::new(value)),
+    }
+}
+
+/// Number of entries by group in the order of the groups
+fn group_counts(list: &[CourseEntry]) -> BTreeMap<Collated<&str>, (usize, f64)> {
+    let mut groups: BTreeMap<Collated<&str>, (usize, f64)> = BTreeMap::new();
+    for entry in list {
+        let (count, price) = groups.entry(Collated(&entry.group)).or_default();
+        *count += 1;
+        *price += entry.price.unwrap_or_default();
+    }
+
+    groups
+}
+
+/// Rows with the total price of every group if there are several and of all
+///
+/// The price is written to the column `price` of a table with `width` columns
+/// and labeled in the column in front of it. Group totals are only formulas if
+/// the group is written to column `group`.
+fn price_totals(
+    list: &[CourseEntry],
+    group: Option<usize>,
+    price: usize,
+    width: usize,
+) -> Vec<Vec<Cell>> {
+    let row = |label: String, total: Cell| {
+        let mut cells = vec![Cell::Text(String::new()); width];
+        if price > 0 {
+            cells[price - 1] = Cell::Text(label);
+        }
+        cells[price] = total;
+        cells
+    };
+    let prices = rows_range(price, list.len());
+
+    let groups = group_counts(list);
+    let mut totals = Vec::new();
+    if groups.len() > 1 {
+        totals.extend(groups.iter().map(|(name, (_, total))| {
+            let total = Cell::Currency(*total);
+            let total = match group {
+                Some(group) => formula(
+                    list,
+                    || {
+                        format!(
+                            "=SUMIF({},{},{})",
+                            rows_range(group, list.len()),
+                            criterion(name.0),
+                            prices
+                        )
+                    },
+                    total,
+                ),
+                None => total,
+            };
+
+            row(format!("Summe {}", name.0), total)
+        }));
+    }
+
+    let total = groups.values().map(|(_, total)| total).sum();
+    totals.push(row(
+        "Gesamt".to_string(),
+        formula(list, || format!("=SUM({})", prices), Cell::Currency(total)),
+    ));
+
+    totals
+}
+
+/// Rows counting the participants of every group if there are several, all
+/// participants and those without telephone number or email address
+///
+/// The counts are formulas if the counted column is written. Labels are
+/// written to the first column of a table with `width` columns, the counts to
+/// the second one.
+fn summary(
+    list: &[CourseEntry],
+    group: Option<usize>,
+    counted: Option<usize>,
+    telephone: Option<usize>,
+    email: Option<usize>,
+    width: usize,
+) -> Vec<Vec<Cell>> {
+    let row = |label: String, count: Cell| {
+        let mut cells = vec![Cell::Text(String::new()); width];
+        cells[0] = Cell::Text(label);
+        cells[1] = count;
+        cells
+    };
+    let count = |count: usize| Cell::Number(count as f64);
+    let count_blank = |column: Option<usize>, missing: usize| match column {
+        Some(column) => formula(
+            list,
+            || format!("=COUNTBLANK({})", rows_range(column, list.len())),
+            count(missing),
+        ),
+        None => count(missing),
+    };
+
+    let groups = group_counts(list);
+    let mut rows = Vec::new();
+    if groups.len() > 1 {
+        rows.extend(groups.iter().map(|(name, (participants, _))| {
+            let participants = match group {
+                Some(group) => formula(
+                    list,
+                    || {
+                        format!(
+                            "=COUNTIF({},{})",
+                            rows_range(group, list.len()),
+                            criterion(name.0)
+                        )
+                    },
+                    count(*participants),
+                ),
+                None => count(*participants),
+            };
+
+            row(format!("Teilnehmer {}", name.0), participants)
+        }));
+    }
+
+    let participants = match counted {
+        Some(column) => formula(
+            list,
+            || {
+                // cells of the column may be empty, e.g. names of rows with a warning
+                let range = rows_range(column, list.len());
+                format!("=COUNTA({0})+COUNTBLANK({0})", range)
+            },
+            count(list.len()),
+        ),
+        None => count(list.len()),
+    };
+    rows.push(row("Teilnehmer gesamt".to_string(), participants));
+    rows.push(row(
+        "Ohne Telefon".to_string(),
+        count_blank(
+            telephone,
+            list.iter()
+                .filter(|entry| entry.telephone.is_empty())
+                .count(),
+        ),
+    ));
+    rows.push(row(
+        "Ohne E-Mail".to_string(),
+        count_blank(
+            email,
+            list.iter().filter(|entry| entry.email.is_empty()).count(),
+        ),
+    ));
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preferences::CsvEncoding;
+    use crate::testing::{course_entry, sheet_range};
+    use chrono::{NaiveDate, Weekday};
+
+    fn header(labels: &[&str]) -> Vec<DataType> {
+        labels
+            .iter()
+            .map(|label| DataType::String(label.to_string()))
+            .collect()
+    }
+
+    /// Course list backed by a single in-memory sheet
+    struct TestSheet(Range<DataType>);
+
+    impl TestSheet {
+        fn new(rows: &[&[&str]]) -> Self {
+            Self::at(0, rows)
+        }
+
+        fn at(first_column: u32, rows: &[&[&str]]) -> Self {
+            Self(sheet_range(first_column, rows))
+        }
+    }
+
+    impl CourseList<Sheets> for TestSheet {
+        fn sheets(&self) -> Option<&[String]> {
+            None
+        }
+
+        fn get_sheet(&mut self, _name: &str) -> Option<Result<Range<DataType>, WorkbookError>> {
+            Some(Ok(self.0.clone()))
+        }
+    }
+
+    const HEADER: &[&str] = &["Kundennummer", "Gruppe", "Name", "Telefon", "E-Mail"];
+
+    #[test]
+    fn locate_header() {
+        let mapping = ColumnMapping::default();
+
+        let indices = HeaderIndices::locate(
+            &header(&[
+                "Kundennummer",
+                "",
+                "Name ",
+                "telefon",
+                "Sonstiges",
+                "E-Mail",
+            ]),
+            0,
+            &mapping,
+            30,
+        )
+        .unwrap();
+        assert_eq!(
+            indices,
+            HeaderIndices {
+                id: 0,
+                name: 2,
+                telephone: 3,
+                email: 5,
+            }
+        );
+
+        match HeaderIndices::locate(
+            &header(&["Kundennummer", "Name", "Telefon"]),
+            0,
+            &mapping,
+            30,
+        ) {
+            Err(CourseListError::MissingHeader { headers, row }) => {
+                assert_eq!(headers, vec!["E-Mail"]);
+                assert_eq!(row, 30);
+            }
+            other => panic!("expected missing header but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn detect_header_row() {
+        let mut sheet = TestSheet::new(&[
+            &["Buchungen"],
+            &[],
+            HEADER,
+            &["1", "Bronze", "Anna", "0751 123", "anna@example.com"],
+            &["2", "", "Ben", "0751 456", "ben@example.com"],
+        ]);
+        let mut options = CourseListOptions::default();
+
+        let (row, labels) = sheet.read_header("", &options.columns).unwrap();
+        assert_eq!(row, 3);
+        assert_eq!(labels[1], (1, "Gruppe".to_string()));
+        assert_eq!(labels.len(), HEADER.len());
+
+        let list = sheet.read_course_list("", "B", &options).unwrap();
+        assert_eq!(list.header_row, 3);
+        assert_eq!(list.entries.len(), 1);
+        assert_eq!(list.entries[0].name, "Anna");
+
+        options.columns.header_row = Some(1);
+        assert!(sheet.read_course_list("", "B", &options).is_err());
+
+        options.columns.header_row = Some(3);
+        assert_eq!(
+            sheet
+                .read_course_list("", "B", &options)
+                .unwrap()
+                .header_row,
+            3
+        );
+
+        // the labels missing in the most complete row are reported
+        let mut sheet = TestSheet::new(&[
+            &["Buchungen", "Name"],
+            &["Kundennummer", "Gruppe", "Name", "Tel.", "Mail"],
+            &["1", "Bronze", "Anna", "0751 123", "anna@example.com"],
+        ]);
+        let options = CourseListOptions::default();
+        match sheet.read_course_list("", "B", &options) {
+            Err(err @ CourseListError::MissingHeader { .. }) => assert_eq!(
+                err.to_string(),
+                "Could not find columns `Telefon`, `E-Mail` in header row 2"
+            ),
+            other => panic!("expected missing header but got {:?}", other),
+        }
+
+        let mut sheet = TestSheet::new(&[&["Buchungen"], &["1", "Bronze", "Anna"]]);
+        assert!(matches!(
+            sheet.read_course_list("", "B", &options),
+            Err(CourseListError::HeaderNotFound)
+        ));
+    }
+
+    #[test]
+    fn read_multiple_columns() {
+        assert_eq!(parse_group_columns("P:R, T").unwrap(), vec![15, 16, 17, 19]);
+        assert_eq!(parse_group_columns("B,B:C").unwrap(), vec![1, 2]);
+        assert!(parse_group_columns("U:P").is_err());
+
+        let mut sheet = TestSheet::new(&[
+            &[
+                "Kundennummer",
+                "Name",
+                "Telefon",
+                "E-Mail",
+                "Mo",
+                "Di",
+                "Mi",
+            ],
+            &["1", "Anna", "", "", "Bronze", "", "Bronze"],
+            &["2", "Ben", "", "", "", "Silber", "Bronze"],
+            &["3", "Cem", "", "", "", "", ""],
+        ]);
+
+        let list = sheet
+            .read_course_list("", "E:G", &CourseListOptions::default())
+            .unwrap();
+        let entries: Vec<_> = list
+            .entries
+            .iter()
+            .map(|entry| (entry.id, entry.group.as_str(), entry.columns.clone()))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (1, "Bronze", vec![4, 6]),
+                (2, "Silber", vec![5]),
+                (2, "Bronze", vec![6]),
+            ]
+        );
+    }
+
+    #[test]
+    fn narrow_sheet() {
+        let rows: &[&[&str]] = &[
+            HEADER,
+            &["1", "Bronze", "Anna", "0751 123", "a@example.com"],
+        ];
+        let mut sheet = TestSheet::new(rows);
+        let mut options = CourseListOptions::default();
+
+        match sheet.read_course_list("", "F:G", &options) {
+            Err(CourseListError::ColumnOutOfRange { column, last, row }) => {
+                assert_eq!((column, last, row), (5, 4, 1));
+            }
+            other => panic!("expected column out of range but got {:?}", other),
+        }
+
+        // trailing empty columns are not part of the used range
+        options.show_price = true;
+        options.price_column = "CX".to_string();
+        options.auxiliaries = vec![AuxiliaryColumn {
+            name: "Notiz".to_string(),
+            column: "CY".to_string(),
+            format: AuxiliaryFormat::Text,
+        }];
+        let list = sheet.read_course_list("", "B, F", &options).unwrap();
+        assert_eq!(list.entries.len(), 1);
+        assert_eq!(list.entries[0].auxiliaries, vec![Cell::Text(String::new())]);
+        assert_eq!(
+            list.issues,
+            vec![Issue::new(2, 101, IssueKind::MissingPrice)]
+        );
+
+        let data = SheetRow {
+            row: 2,
+            first_column: 1,
+            cells: &header(&["1", "Bronze"]),
+        };
+        assert_eq!(data.get(0), &DataType::Empty);
+        assert_eq!(data.get(2), &DataType::String("Bronze".to_string()));
+        assert_eq!(data.get(3), &DataType::Empty);
+
+        // the used range starts in column C
+        let mut sheet = TestSheet::at(2, rows);
+        let list = sheet
+            .read_course_list("", "D", &CourseListOptions::default())
+            .unwrap();
+        assert_eq!(list.header.id, 2);
+        assert_eq!(list.entries[0].name, "Anna");
+        assert!(sheet
+            .read_course_list("", "A", &CourseListOptions::default())
+            .unwrap()
+            .entries
+            .is_empty());
+    }
+
+    #[test]
+    fn auxiliary_columns_stay_aligned() {
+        let mut sheet = TestSheet::new(&[
+            &[
+                "Kundennummer",
+                "Gruppe",
+                "Name",
+                "Telefon",
+                "E-Mail",
+                "Notiz",
+                "Alter",
+            ],
+            &["1", "Bronze", "Anna", "0751 123", "a@example.com", "", "7"],
+        ]);
+        let aux = |column: &str, format| AuxiliaryColumn {
+            name: column.to_string(),
+            column: column.to_string(),
+            format,
+        };
+        let mut options = CourseListOptions {
+            auxiliaries: vec![
+                aux("F", AuxiliaryFormat::Text),
+                aux("G", AuxiliaryFormat::Number),
+            ],
+            ..CourseListOptions::default()
+        };
+
+        let list = sheet.read_course_list("", "B", &options).unwrap();
+        assert_eq!(
+            list.entries[0].auxiliaries,
+            vec![Cell::Text(String::new()), Cell::Number(7.)]
+        );
+        assert!(list.issues.is_empty());
+
+        options.auxiliaries[1].format = AuxiliaryFormat::Date;
+        let list = sheet.read_course_list("", "B", &options).unwrap();
+        assert_eq!(list.entries[0].auxiliaries[1], Cell::Text("7".to_string()));
+        assert_eq!(
+            list.issues,
+            vec![Issue::new(
+                2,
+                6,
+                IssueKind::InvalidCell(CellError::NotADate("7".to_string()))
+            )]
+        );
+
+        for invalid in &["A1", "", "f"] {
+            options.auxiliaries = vec![
+                aux("F", AuxiliaryFormat::Text),
+                aux(invalid, AuxiliaryFormat::Text),
+                aux("G", AuxiliaryFormat::Text),
+            ];
+            match sheet.read_course_list("", "B", &options) {
+                Err(CourseListError::InvalidAuxiliary { column, .. }) => {
+                    assert_eq!(column, *invalid)
+                }
+                other => panic!("expected invalid auxiliary but got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn validation_report() {
+        let mut sheet = TestSheet::new(&[
+            HEADER,
+            &["1", "Bronze", "Anna", "0751 123", "anna@example"],
+            &["x", "Bronze", "Ben", "0751 456", "ben@example.com"],
+            &["3", "Bronze", "", "", "cem@example.com"],
+            &["4", "Silber", "Dora", "0751 789\nOma", "dora@example.com"],
+        ]);
+        let mut options = CourseListOptions::default();
+
+        match sheet.read_course_list("", "B", &options) {
+            Err(CourseListError::InvalidRows(issues)) => {
+                assert_eq!(
+                    issues,
+                    vec![Issue::new(
+                        3,
+                        0,
+                        IssueKind::InvalidId(CellError::NotAnInteger("x".to_string()))
+                    )]
+                );
+            }
+            other => panic!("expected invalid rows but got {:?}", other),
+        }
+
+        options.skip_invalid_rows = true;
+        let list = sheet.read_course_list("", "B", &options).unwrap();
+        assert_eq!(list.entries.len(), 3);
+        assert_eq!(list.skipped_rows(), 1);
+        assert_eq!(
+            list.issues
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "Row 2, column E: `anna@example`: Domain `example` has no top level domain",
+                "Row 3, column A: Invalid customer id: Expected a whole number but found `x`",
+                "Row 4, column C: no name",
+                "Row 4, column D: no telephone number",
+                "Row 5, column D: `Oma`: Invalid character `O` in telephone number",
+            ]
+        );
+        assert_eq!(list.issues_of(&list.entries[1]).count(), 2);
+        assert_eq!(list.entries[0].email, vec!["anna@example"]);
+
+        options.exclude_invalid_emails = true;
+        let list = sheet.read_course_list("", "B", &options).unwrap();
+        assert!(list.entries[0].email.is_empty());
+    }
+
+    #[test]
+    fn custom_base_columns() {
+        let entry = |id, column| CourseEntry {
+            columns: vec![column],
+            ..course_entry(id, "Bronze", "Anna")
+        };
+        let list = vec![entry(1, 1), entry(2, 2)];
+        let mut options = CourseListOptions {
+            base_columns: "name, group=Kurs".parse().unwrap(),
+            ..CourseListOptions::default()
+        };
+
+        let table = preview_table(&list, &options);
+        let titles: Vec<&str> = table.columns.iter().map(|col| col.title.as_str()).collect();
+        assert_eq!(titles, vec!["Name", "Kurs", "Spalte"]);
+        assert_eq!(
+            table.rows[1],
+            vec![
+                Cell::Text("Anna".to_string()),
+                Cell::Text("Bronze".to_string()),
+                Cell::Text("C".to_string()),
+            ]
+        );
+
+        options.base_columns = "id".parse().unwrap();
+        let table = preview_table(&list[..1], &options);
+        assert_eq!(table.columns.len(), 1);
+        assert_eq!(table.rows[0], vec![Cell::Number(1.)]);
+    }
+
+    #[test]
+    fn price_totals() {
+        let mut sheet = TestSheet::new(&[
+            &[
+                "Kundennummer",
+                "Gruppe",
+                "Name",
+                "Telefon",
+                "E-Mail",
+                "Preis",
+            ],
+            &["1", "Bronze", "Anna", "0751 123", "a@example.com", "80,5"],
+            &["2", "Silber", "Ben", "0751 456", "b@example.com", "95"],
+            &["3", "Bronze", "Cem", "0751 789", "c@example.com", ""],
+        ]);
+        let mut options = CourseListOptions {
+            show_price: true,
+            base_columns: "id, group".parse().unwrap(),
+            ..CourseListOptions::default()
+        };
+
+        assert!(matches!(
+            sheet.read_course_list("", "B", &options),
+            Err(CourseListError::InvalidPriceColumn(ReferenceError::Empty))
+        ));
+
+        options.price_column = "F".to_string();
+        let list = sheet.read_course_list("", "B", &options).unwrap();
+        assert_eq!(list.issues, vec![Issue::new(4, 5, IssueKind::MissingPrice)]);
+
+        let table = preview_table(&list.entries, &options);
+        assert_eq!(table.title, None);
+        assert_eq!(table.columns[2].title, "Preis");
+        assert_eq!(table.rows[0][2], Cell::Currency(80.5));
+        assert_eq!(table.rows[2][2], Cell::Text(String::new()));
+
+        let text = |s: &str| Cell::Text(s.to_string());
+        let formula = |s: &str, value| Cell::Formula(s.to_string(), Box::new(value));
+        assert_eq!(
+            table.totals,
+            vec![
+                vec![
+                    text(""),
+                    text("Summe Bronze"),
+                    formula("=SUMIF(B2:B4,\"=Bronze\",C2:C4)", Cell::Currency(80.5))
+                ],
+                vec![
+                    text(""),
+                    text("Summe Silber"),
+                    formula("=SUMIF(B2:B4,\"=Silber\",C2:C4)", Cell::Currency(95.))
+                ],
+                vec![
+                    text(""),
+                    text("Gesamt"),
+                    formula("=SUM(C2:C4)", Cell::Currency(175.5))
+                ],
+            ]
+        );
+
+        let bronze: Vec<_> = list
+            .entries
+            .into_iter()
+            .filter(|entry| entry.group == "Bronze")
+            .collect();
+        let table = preview_table(&bronze, &options);
+        assert_eq!(table.totals.len(), 1);
+        assert_eq!(table.title.as_deref(), Some("Bronze"));
+    }
+
+    #[test]
+    fn summary_formulas() {
+        let entry = |group: &str, telephone: &[&str]| CourseEntry {
+            telephone: telephone.iter().map(ToString::to_string).collect(),
+            ..course_entry(1, group, "Anna")
+        };
+        let list = vec![
+            entry("Mo \"16*\"", &["0751 123"]),
+            entry("Di", &[]),
+            entry("Di", &["0751 456"]),
+        ];
+        let options = CourseListOptions {
+            base_columns: "name, group, telephone".parse().unwrap(),
+            ..CourseListOptions::default()
+        };
+
+        let summary: Vec<(String, String)> = preview_table(&list, &options)
+            .summary
+            .iter()
+            .map(|cells| {
+                let formula = match &cells[1] {
+                    Cell::Formula(formula, _) => formula.clone(),
+                    cell => cell.to_string(),
+                };
+                (format!("{}: {}", cells[0], cells[1]), formula)
+            })
+            .collect();
+        let row = |label: &str, formula: &str| (label.to_string(), formula.to_string());
+        assert_eq!(
+            summary,
+            vec![
+                row("Teilnehmer Di: 2", "=COUNTIF(B2:B4,\"=Di\")"),
+                row(
+                    "Teilnehmer Mo \"16*\": 1",
+                    "=COUNTIF(B2:B4,\"=Mo \"\"16~*\"\"\")"
+                ),
+                row("Teilnehmer gesamt: 3", "=COUNTA(A2:A4)+COUNTBLANK(A2:A4)"),
+                row("Ohne Telefon: 1", "=COUNTBLANK(C2:C4)"),
+                row("Ohne E-Mail: 3", "3"),
+            ]
+        );
+        assert_eq!(preview_table(&[], &options).summary[0][1], Cell::Number(0.));
+
+        // rows without a name are only warned about and still counted
+        let list = vec![course_entry(1, "Di", "Anna"), course_entry(2, "Di", "")];
+        let options = CourseListOptions {
+            base_columns: "name, telephone".parse().unwrap(),
+            ..CourseListOptions::default()
+        };
+        assert_eq!(
+            preview_table(&list, &options).summary[0][1],
+            Cell::Formula(
+                "=COUNTA(A2:A3)+COUNTBLANK(A2:A3)".to_string(),
+                Box::new(Cell::Number(2.))
+            )
+        );
+    }
+
+    #[test]
+    fn sort_order() {
+        let entry = |id, group: &str, name: &str, age| CourseEntry {
+            auxiliaries: vec![Cell::Number(age)],
+            ..course_entry(id, group, name)
+        };
+        let mut list = vec![
+            entry(1, "Silber", "Zoe Ähnlich", 9.),
+            entry(2, "Bronze", "Ömer Berg", 7.),
+            entry(3, "Bronze", "Otto Acker", 12.),
+            entry(4, "Silber", "Otto Acker", 8.),
+        ];
+        let ids = |list: &[CourseEntry]| list.iter().map(|entry| entry.id).collect::<Vec<_>>();
+        let mut sort = |key, descending| {
+            sort_course_list(&mut list, Sorting { key, descending });
+            ids(&list)
+        };
+
+        assert_eq!(sort(SortKey::Name, false), vec![2, 3, 4, 1]);
+        assert_eq!(sort(SortKey::GroupName, false), vec![2, 3, 4, 1]);
+        assert_eq!(sort(SortKey::Surname, false), vec![3, 4, 1, 2]);
+        assert_eq!(sort(SortKey::Id, true), vec![4, 3, 2, 1]);
+        assert_eq!(sort(SortKey::Auxiliary(0), false), vec![2, 4, 1, 3]);
+        assert_eq!(sort(SortKey::Name, true), vec![1, 4, 3, 2]);
+    }
+
+    #[test]
+    fn write_csv_encoded() {
+        let path = std::env::temp_dir().join("sir_write_csv_encoded.csv");
+        let path = path.to_str().unwrap();
+        let list = vec![CourseEntry {
+            telephone: vec!["0751 123".to_string()],
+            email: vec!["j@example.com".to_string()],
+            auxiliaries: vec![Cell::Text("a;b".to_string())],
+            ..course_entry(7, "Seepferdchen", "Jürgen Groß")
+        }];
+        let mut options = CourseListOptions {
+            auxiliaries: vec![AuxiliaryColumn {
+                name: "Notiz".to_string(),
+                column: "M".to_string(),
+                format: AuxiliaryFormat::Text,
+            }],
+            format: OutputFormat::Csv,
+            ..CourseListOptions::default()
+        };
+
+        WorkbookManager::write_course_list(path, list, &options).unwrap();
+        let expected = "Kundennummer;Gruppe;Name;Telefon;E-Mail;Notiz\r\n\
+                        7;Seepferdchen;Jürgen Groß;0751 123;j@example.com;\"a;b\"\r\n";
+        assert_eq!(
+            std::fs::read(path).unwrap(),
+            [&b"\xEF\xBB\xBF"[..], expected.as_bytes()].concat()
+        );
+
+        // german spreadsheet applications expect a decimal comma
+        options.show_price = true;
+        let list = vec![CourseEntry {
+            price: Some(80.5),
+            auxiliaries: vec![Cell::Text(String::new())],
+            ..course_entry(7, "Seepferdchen", "Anna")
+        }];
+        WorkbookManager::write_course_list(path, list, &options).unwrap();
+        let data = std::fs::read_to_string(path).unwrap();
+        assert!(data.ends_with("\r\n7;Seepferdchen;Anna;;;80,50;\r\n"));
+
+        options.csv.encoding = CsvEncoding::Windows1252;
+        options.csv.delimiter = ',';
+        options.price_column = "N".to_string();
+        let list = vec![CourseEntry {
+            price: Some(95.),
+            auxiliaries: vec![Cell::Text(String::new())],
+            ..course_entry(7, "", "Jürgen Groß")
+        }];
+        WorkbookManager::write_course_list(path, list, &options).unwrap();
+        let data = std::fs::read(path).unwrap();
+        assert!(data.ends_with(b"7,,J\xFCrgen Gro\xDF,,,95.00,\r\n"));
+
+        let list = || {
+            vec![CourseEntry {
+                auxiliaries: vec![Cell::Text(String::new())],
+                ..course_entry(7, "", "Łukasz Nowak")
+            }]
+        };
+        assert!(matches!(
+            WorkbookManager::write_course_list(path, list(), &options),
+            Err(CourseListError::Output(OutputError::UnmappableCharacter {
+                row: 2,
+                character: 'Ł'
+            }))
+        ));
+        for delimiter in &['"', '\n', '\r', '§'] {
+            options.csv.delimiter = *delimiter;
+            assert!(matches!(
+                WorkbookManager::write_course_list(path, list(), &options),
+                Err(CourseListError::Output(OutputError::InvalidDelimiter(_)))
+            ));
+        }
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn attendance_sheets() {
+        let path = std::env::temp_dir().join("sir_attendance_sheets.csv");
+        let path = path.to_str().unwrap();
+        let list = || {
+            vec![
+                course_entry(2, "Mo", "Otto Acker"),
+                course_entry(1, "Mo", "Anna Berg"),
+            ]
+        };
+        let mut options = CourseListOptions {
+            format: OutputFormat::Csv,
+            list_kind: ListKind::Attendance,
+            ..CourseListOptions::default()
+        };
+
+        assert!(matches!(
+            WorkbookManager::write_course_list(path, list(), &options),
+            Err(CourseListError::MissingCourseStart)
+        ));
+
+        options.attendance = AttendanceOptions {
+            start: Some(NaiveDate::from_ymd(2020, 12, 10)),
+            weekday: Weekday::Mon,
+            lessons: 3,
+            holidays: vec!["21.12.2020-06.01.2021".parse().unwrap()],
+        };
+        let table = preview_table(&list(), &options);
+        assert_eq!(table.title.as_deref(), Some("Mo"));
+        assert!(table.summary.is_empty());
+
+        WorkbookManager::write_course_list(path, list(), &options).unwrap();
+        let expected = "Kundennummer;Name;14.12.20;11.01.21;18.01.21\r\n\
+                        1;Anna Berg;;;\r\n\
+                        2;Otto Acker;;;\r\n";
+        assert_eq!(
+            std::fs::read(path).unwrap(),
+            [&b"\xEF\xBB\xBF"[..], expected.as_bytes()].concat()
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
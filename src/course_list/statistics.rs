@@ -0,0 +1,432 @@
+//! Statistics of filled-in sign-in sheets
+
+use super::attendance::{ID_HEADER, NAME_HEADER};
+use super::{read_customer_id, CustomerId};
+use crate::{
+    collation::{self, Collated},
+    output::{Cell, Table, TableColumn},
+    workbook::{CellError, CellValue, WorkbookError, WorkbookManager},
+};
+use calamine::{DataType, Range};
+use chrono::NaiveDate;
+use log::warn;
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Marks of instructors meaning that a participant missed a lesson
+const ABSENT_MARKS: &[&str] = &["-", "0", "a", "f", "n", "nein", "false"];
+
+#[derive(Debug, Error)]
+pub enum AttendanceError {
+    #[error("No Worksheet loaded")]
+    NoReader,
+
+    #[error("Could not read sheet: {0}")]
+    Workbook(#[source] WorkbookError),
+
+    #[error(
+        "No sheet with the columns `{}` and `{}` found",
+        ID_HEADER,
+        NAME_HEADER
+    )]
+    NoAttendanceSheet,
+
+    #[error("Invalid customer id in sheet {sheet} row {row}: {source}")]
+    InvalidId {
+        sheet: String,
+        row: usize,
+        #[source]
+        source: CellError,
+    },
+}
+
+/// Whether a participant was at a lesson
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Presence {
+    pub date: NaiveDate,
+    pub present: bool,
+}
+
+/// Lessons of a participant read from a filled-in sign-in sheet
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParticipantAttendance {
+    pub id: CustomerId,
+    pub name: String,
+    /// Name of the sheet the participant was read from
+    pub group: String,
+    /// Lessons which took place ordered by date
+    pub lessons: Vec<Presence>,
+}
+
+impl ParticipantAttendance {
+    pub fn attended(&self) -> usize {
+        self.lessons.iter().filter(|lesson| lesson.present).count()
+    }
+
+    /// Most lessons missed one after another
+    pub fn missed_in_row(&self) -> usize {
+        self.lessons
+            .iter()
+            .fold((0, 0), |(longest, current), lesson| match lesson.present {
+                true => (longest, 0),
+                false => (longest.max(current + 1), current + 1),
+            })
+            .0
+    }
+}
+
+/// Reader of filled-in sign-in sheets as written by `attendance_table`
+pub trait AttendanceSheets {
+    fn sheets(&self) -> Option<&[String]>;
+    fn get_sheet(&mut self, name: &str) -> Option<Result<Range<DataType>, WorkbookError>>;
+
+    /// Reads the participants of every sheet with their lessons
+    ///
+    /// The sheet names are taken as the groups. Sheets without the header of a
+    /// sign-in sheet are left out.
+    fn read_attendance(&mut self) -> Result<Vec<ParticipantAttendance>, AttendanceError> {
+        let sheets = self.sheets().ok_or(AttendanceError::NoReader)?.to_vec();
+        let mut participants = Vec::new();
+        let mut found = false;
+
+        for sheet in sheets {
+            let range = self
+                .get_sheet(&sheet)
+                .ok_or(AttendanceError::NoReader)?
+                .map_err(|err| AttendanceError::Workbook(err))?;
+
+            match read_sheet(&sheet, &range)? {
+                Some(sheet) => {
+                    participants.extend(sheet);
+                    found = true;
+                }
+                None => warn!("Sheet {} is no sign-in sheet", sheet),
+            }
+        }
+
+        match found {
+            true => Ok(participants),
+            false => Err(AttendanceError::NoAttendanceSheet),
+        }
+    }
+}
+
+impl AttendanceSheets for WorkbookManager {
+    fn sheets(&self) -> Option<&[String]> {
+        self.sheets()
+    }
+
+    fn get_sheet(&mut self, name: &str) -> Option<Result<Range<DataType>, WorkbookError>> {
+        self.get_sheet(name)
+    }
+}
+
+fn is_marked(cell: &DataType) -> bool {
+    !cell.to_string().trim().is_empty()
+}
+
+fn is_present(cell: &DataType) -> bool {
+    let mark = cell.to_string().trim().to_lowercase();
+    !mark.is_empty() && !ABSENT_MARKS.contains(&mark.as_str())
+}
+
+/// Reads a single sign-in sheet or returns `None` if it has no header
+///
+/// Lessons without any mark did not take place yet and are left out. Empty
+/// cells of the other lessons count as missed.
+fn read_sheet(
+    sheet: &str,
+    range: &Range<DataType>,
+) -> Result<Option<Vec<ParticipantAttendance>>, AttendanceError> {
+    let first_row = range.start().map_or(0, |(row, _)| row as usize);
+    let mut rows = range.rows().enumerate();
+
+    let header = rows.find_map(|(_, cells)| {
+        let position = |label| {
+            cells
+                .iter()
+                .position(|cell| cell.to_string().trim() == label)
+        };
+        Some((position(ID_HEADER)?, position(NAME_HEADER)?, cells))
+    });
+    let (id_column, name_column, header) = match header {
+        Some(header) => header,
+        None => return Ok(None),
+    };
+
+    let entries: Vec<(usize, &[DataType])> = rows
+        .filter(|(_, cells)| is_marked(&cells[id_column]) || is_marked(&cells[name_column]))
+        .collect();
+
+    let mut lessons: Vec<(usize, NaiveDate)> = header
+        .iter()
+        .enumerate()
+        .filter_map(|(column, cell)| Some((column, cell.date().ok()?)))
+        .filter(|(column, _)| entries.iter().any(|(_, cells)| is_marked(&cells[*column])))
+        .collect();
+    lessons.sort_by_key(|(_, date)| *date);
+
+    entries
+        .into_iter()
+        .map(|(index, cells)| {
+            let id = read_customer_id(&cells[id_column]).map_err(|source| {
+                AttendanceError::InvalidId {
+                    sheet: sheet.to_string(),
+                    row: first_row + index + 1,
+                    source,
+                }
+            })?;
+
+            Ok(ParticipantAttendance {
+                id,
+                name: cells[name_column].to_string().trim().to_string(),
+                group: sheet.to_string(),
+                lessons: lessons
+                    .iter()
+                    .map(|(column, date)| Presence {
+                        date: *date,
+                        present: is_present(&cells[*column]),
+                    })
+                    .collect(),
+            })
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+/// Attendance in percent rounded to one decimal place
+fn rate(attended: usize, held: usize) -> Cell {
+    match held {
+        0 => Cell::Text(String::new()),
+        held => Cell::Number((attended as f64 / held as f64 * 1000.).round() / 10.),
+    }
+}
+
+/// Converts the lessons of all participants into a report with their rate of
+/// attendance and the rates of every group and all groups below
+///
+/// The rates of the groups are rows of the report like the participants so
+/// they are written to CSV files as well. Participants who missed at least `missed_in_row` lessons one after another
+/// are noted unless it is 0.
+pub fn attendance_report(participants: &[ParticipantAttendance], missed_in_row: usize) -> Table {
+    let mut participants: Vec<&ParticipantAttendance> = participants.iter().collect();
+    participants.sort_by(|a, b| {
+        collation::compare(&a.group, &b.group).then_with(|| collation::compare(&a.name, &b.name))
+    });
+
+    let columns = vec![
+        TableColumn::new(ID_HEADER, 5.).centered(),
+        TableColumn::new(NAME_HEADER, 25.),
+        TableColumn::new("Gruppe", 15.),
+        TableColumn::new("Anwesend", 10.).centered(),
+        TableColumn::new("Stunden", 10.).centered(),
+        TableColumn::new("Quote %", 10.).centered(),
+        TableColumn::new("Fehlt in Folge", 14.).centered(),
+        TableColumn::new("Hinweis", 30.),
+    ];
+
+    let mut rows: Vec<Vec<Cell>> = participants
+        .iter()
+        .map(|participant| {
+            let missed = participant.missed_in_row();
+            let note = match missed_in_row {
+                0 => String::new(),
+                limit if missed >= limit => format!("{} Stunden in Folge gefehlt", missed),
+                _ => String::new(),
+            };
+
+            vec![
+                Cell::Number(participant.id.into()),
+                Cell::Text(participant.name.clone()),
+                Cell::Text(participant.group.clone()),
+                Cell::Number(participant.attended() as f64),
+                Cell::Number(participant.lessons.len() as f64),
+                rate(participant.attended(), participant.lessons.len()),
+                Cell::Number(missed as f64),
+                Cell::Text(note),
+            ]
+        })
+        .collect();
+
+    let mut groups: BTreeMap<Collated<&str>, (usize, usize)> = BTreeMap::new();
+    for participant in &participants {
+        let (attended, held) = groups.entry(Collated(&participant.group)).or_default();
+        *attended += participant.attended();
+        *held += participant.lessons.len();
+    }
+    let total = groups.values().fold((0, 0), |(attended, held), group| {
+        (attended + group.0, held + group.1)
+    });
+
+    rows.extend(
+        groups
+            .into_iter()
+            .map(|(group, counts)| (group.0, counts))
+            .chain((!participants.is_empty()).then_some(("", total)))
+            .map(|(group, (attended, held))| {
+                vec![
+                    Cell::Text(String::new()),
+                    Cell::Text("Gesamt".to_string()),
+                    Cell::Text(group.to_string()),
+                    Cell::Number(attended as f64),
+                    Cell::Number(held as f64),
+                    rate(attended, held),
+                    Cell::Text(String::new()),
+                    Cell::Text(String::new()),
+                ]
+            }),
+    );
+
+    Table {
+        name: Some("Anwesenheit".to_string()),
+        title: Some("Anwesenheit".to_string()),
+        columns,
+        rows,
+        totals: Vec::new(),
+        summary: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        output::{CourseListWriter, CsvWriter},
+        preferences::CsvOptions,
+        testing::sheet_range,
+    };
+
+    /// Workbook with in-memory sheets
+    struct TestWorkbook(Vec<String>, Vec<Range<DataType>>);
+
+    impl TestWorkbook {
+        fn new(sheets: &[(&str, &[&[&str]])]) -> Self {
+            let ranges = sheets
+                .iter()
+                .map(|(_, rows)| sheet_range(0, rows))
+                .collect();
+
+            Self(
+                sheets.iter().map(|(name, _)| name.to_string()).collect(),
+                ranges,
+            )
+        }
+    }
+
+    impl AttendanceSheets for TestWorkbook {
+        fn sheets(&self) -> Option<&[String]> {
+            Some(&self.0)
+        }
+
+        fn get_sheet(&mut self, name: &str) -> Option<Result<Range<DataType>, WorkbookError>> {
+            let index = self.0.iter().position(|sheet| sheet == name)?;
+            Some(Ok(self.1[index].clone()))
+        }
+    }
+
+    fn date(d: u32, m: u32) -> NaiveDate {
+        NaiveDate::from_ymd(2021, m, d)
+    }
+
+    #[test]
+    fn read_filled_in_sheets() {
+        let mut workbook = TestWorkbook::new(&[
+            (
+                "Mo",
+                &[
+                    &["Kundennummer", "Name", "18.01.21", "11.01.21", "25.01.21"],
+                    &["1", "Anna Berg", "x", "x", ""],
+                    &["2", "Otto Acker", "", "f", ""],
+                    &["", "", "", "", ""],
+                ],
+            ),
+            ("Notizen", &[&["Halle 2"]]),
+        ]);
+
+        let participants = workbook.read_attendance().unwrap();
+        assert_eq!(participants.len(), 2);
+        assert_eq!(participants[0].group, "Mo");
+        assert_eq!(
+            participants[0].lessons,
+            vec![
+                Presence {
+                    date: date(11, 1),
+                    present: true
+                },
+                Presence {
+                    date: date(18, 1),
+                    present: true
+                },
+            ]
+        );
+        assert_eq!(participants[1].attended(), 0);
+        assert_eq!(participants[1].missed_in_row(), 2);
+
+        let mut workbook = TestWorkbook::new(&[("Mo", &[&["Kundennummer", "Name"], &["x", "A"]])]);
+        assert!(matches!(
+            workbook.read_attendance(),
+            Err(AttendanceError::InvalidId { row: 2, .. })
+        ));
+        let mut workbook = TestWorkbook::new(&[("Notizen", &[&["Halle 2"]])]);
+        assert!(matches!(
+            workbook.read_attendance(),
+            Err(AttendanceError::NoAttendanceSheet)
+        ));
+    }
+
+    #[test]
+    fn report() {
+        let participant = |id, group: &str, presence: &[bool]| ParticipantAttendance {
+            id,
+            name: format!("Kind {}", id),
+            group: group.to_string(),
+            lessons: presence
+                .iter()
+                .enumerate()
+                .map(|(i, present)| Presence {
+                    date: date(i as u32 + 1, 2),
+                    present: *present,
+                })
+                .collect(),
+        };
+        let participants = vec![
+            participant(1, "Mo", &[true, false, false, false, true]),
+            participant(2, "Di", &[true, true, false, true]),
+            participant(3, "Mo", &[true, true, true, true, true]),
+        ];
+
+        let table = attendance_report(&participants, 3);
+        let text = |cells: &[Cell]| cells.iter().map(Cell::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            table.rows.iter().map(|row| text(row)).collect::<Vec<_>>(),
+            vec![
+                vec!["2", "Kind 2", "Di", "3", "4", "75", "1", ""],
+                vec![
+                    "1",
+                    "Kind 1",
+                    "Mo",
+                    "2",
+                    "5",
+                    "40",
+                    "3",
+                    "3 Stunden in Folge gefehlt"
+                ],
+                vec!["3", "Kind 3", "Mo", "5", "5", "100", "0", ""],
+                vec!["", "Gesamt", "Di", "3", "4", "75", "", ""],
+                vec!["", "Gesamt", "Mo", "7", "10", "70", "", ""],
+                vec!["", "Gesamt", "", "10", "14", "71.4", "", ""],
+            ]
+        );
+        assert!(attendance_report(&[], 3).rows.is_empty());
+
+        let path = std::env::temp_dir().join("sir_attendance_report.csv");
+        CsvWriter::new(CsvOptions::default())
+            .write(&path, &[table])
+            .unwrap();
+        let report = std::fs::read_to_string(&path).unwrap();
+        assert!(report.contains("\r\n;Gesamt;Di;3;4;75;;\r\n"));
+        assert!(report.ends_with("\r\n;Gesamt;;10;14;71,4;;\r\n"));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,65 @@
+//! Problems found in single rows of the source sheet
+
+use crate::{email::EmailError, phone::PhoneError, workbook::CellError, ColumnName};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IssueKind {
+    /// The customer id is not a number, the row is not read
+    InvalidId(CellError),
+    /// The cell can not be read, e.g. because it contains an error
+    InvalidCell(CellError),
+    MissingName,
+    MissingTelephone,
+    InvalidTelephone(String, PhoneError),
+    MissingEmail,
+    InvalidEmail(String, EmailError),
+    MissingPrice,
+}
+
+/// Problem of a single cell of the source sheet
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    /// Row in the sheet starting at 1
+    pub row: usize,
+    pub column: usize,
+    pub kind: IssueKind,
+}
+
+impl Issue {
+    pub fn new(row: usize, column: usize, kind: IssueKind) -> Self {
+        Self { row, column, kind }
+    }
+
+    /// Whether the row can not be read because of this issue
+    pub fn is_error(&self) -> bool {
+        matches!(self.kind, IssueKind::InvalidId(_))
+    }
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IssueKind::InvalidId(err) => write!(f, "Invalid customer id: {}", err),
+            IssueKind::InvalidCell(err) => write!(f, "{}", err),
+            IssueKind::MissingName => write!(f, "no name"),
+            IssueKind::MissingTelephone => write!(f, "no telephone number"),
+            IssueKind::InvalidTelephone(number, err) => write!(f, "`{}`: {}", number, err),
+            IssueKind::MissingEmail => write!(f, "no email address"),
+            IssueKind::InvalidEmail(email, err) => write!(f, "`{}`: {}", email, err),
+            IssueKind::MissingPrice => write!(f, "no price"),
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Row {}, column {}: {}",
+            self.row,
+            self.column.to_column_name(),
+            self.kind
+        )
+    }
+}
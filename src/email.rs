@@ -0,0 +1,209 @@
+//! Email addresses as written into the booking sheet
+//!
+//! Only the syntax is checked, loosely following RFC 5322 without quoted or
+//! commented parts which are never used by our customers.
+
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum EmailError {
+    #[error("Email address is empty")]
+    Empty,
+
+    #[error("Email address has no `@`")]
+    MissingAt,
+
+    #[error("Email address has more than one `@`")]
+    MultipleAt,
+
+    #[error("Email address has nothing in front of the `@`")]
+    EmptyLocalPart,
+
+    #[error("Invalid character `{0}` in email address")]
+    InvalidCharacter(char),
+
+    #[error("Invalid domain `{0}`")]
+    InvalidDomain(String),
+
+    #[error("Domain `{0}` has no top level domain")]
+    MissingTopLevelDomain(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailAddress {
+    local: String,
+    /// Domain in lower case
+    domain: String,
+}
+
+impl EmailAddress {
+    pub fn local_part(&self) -> &str {
+        &self.local
+    }
+
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+}
+
+impl FromStr for EmailAddress {
+    type Err = EmailError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(EmailError::Empty);
+        }
+
+        let mut parts = s.split('@');
+        let (local, domain) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(local), Some(domain), None) => (local, domain),
+            (_, None, _) => return Err(EmailError::MissingAt),
+            _ => return Err(EmailError::MultipleAt),
+        };
+
+        if local.is_empty() {
+            return Err(EmailError::EmptyLocalPart);
+        }
+        if let Some(c) = local
+            .chars()
+            .find(|&c| !(c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~.".contains(c)))
+        {
+            return Err(EmailError::InvalidCharacter(c));
+        }
+        if local.starts_with('.') || local.ends_with('.') || local.contains("..") {
+            return Err(EmailError::InvalidCharacter('.'));
+        }
+
+        let domain = domain.to_lowercase();
+        let labels: Vec<&str> = domain.split('.').collect();
+        let valid_label = |label: &&str| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        };
+        if !labels.iter().all(valid_label) {
+            return Err(EmailError::InvalidDomain(domain));
+        }
+
+        let tld = labels[labels.len() - 1];
+        if labels.len() < 2 || tld.len() < 2 || !tld.chars().all(char::is_alphabetic) {
+            return Err(EmailError::MissingTopLevelDomain(domain));
+        }
+
+        Ok(Self {
+            local: local.to_string(),
+            domain,
+        })
+    }
+}
+
+impl fmt::Display for EmailAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.local, self.domain)
+    }
+}
+
+/// Splits a cell holding several addresses on semicolons, commas and line breaks
+///
+/// Every part is parsed on its own after leaving out a display name as in
+/// `Anna Huber <anna@example.com>`, the written text is kept for invalid ones.
+pub fn parse_addresses(s: &str) -> Vec<Result<EmailAddress, (String, EmailError)>> {
+    s.split(&[';', ',', '\n', '\r'][..])
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            address_spec(part)
+                .parse()
+                .map_err(|err| (part.to_string(), err))
+        })
+        .collect()
+}
+
+/// The address within angle brackets if `s` is written with a display name
+fn address_spec(s: &str) -> &str {
+    match (s.rfind('<'), s.strip_suffix('>')) {
+        (Some(start), Some(rest)) => &rest[start + 1..],
+        _ => s,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_addresses() {
+        let address: EmailAddress = " Anna.Huber+kurs@Example.COM ".parse().unwrap();
+        assert_eq!(address.local_part(), "Anna.Huber+kurs");
+        assert_eq!(address.domain(), "example.com");
+        assert_eq!(address.to_string(), "Anna.Huber+kurs@example.com");
+        assert!("j@schwimmen-im-ried.de".parse::<EmailAddress>().is_ok());
+        assert!("j@müller.de".parse::<EmailAddress>().is_ok());
+    }
+
+    #[test]
+    fn invalid_addresses() {
+        let parse = |s: &str| s.parse::<EmailAddress>().unwrap_err();
+
+        assert_eq!(parse(""), EmailError::Empty);
+        assert_eq!(parse("anna.example.com"), EmailError::MissingAt);
+        assert_eq!(parse("anna@@example.com"), EmailError::MultipleAt);
+        assert_eq!(parse("@example.com"), EmailError::EmptyLocalPart);
+        assert_eq!(
+            parse("an\"na@example.com"),
+            EmailError::InvalidCharacter('"')
+        );
+        assert_eq!(
+            parse("anna.@example.com"),
+            EmailError::InvalidCharacter('.')
+        );
+        assert_eq!(
+            parse("anna@example..com"),
+            EmailError::InvalidDomain("example..com".to_string())
+        );
+        assert_eq!(
+            parse("anna@example"),
+            EmailError::MissingTopLevelDomain("example".to_string())
+        );
+        assert_eq!(
+            parse("anna@example.c"),
+            EmailError::MissingTopLevelDomain("example.c".to_string())
+        );
+    }
+
+    #[test]
+    fn several_addresses() {
+        let addresses = parse_addresses("anna@example.com; ben@example.com,\r\nbla");
+        assert_eq!(addresses.len(), 3);
+        assert_eq!(
+            addresses[1].as_ref().unwrap().to_string(),
+            "ben@example.com"
+        );
+        assert_eq!(
+            addresses[2],
+            Err(("bla".to_string(), EmailError::MissingAt))
+        );
+    }
+
+    #[test]
+    fn display_names() {
+        let addresses =
+            parse_addresses("Anna Huber <anna@example.com>;\n<ben@example.com>, Carl <carl>");
+        assert_eq!(addresses.len(), 3);
+        assert_eq!(
+            addresses[0].as_ref().unwrap().to_string(),
+            "anna@example.com"
+        );
+        assert_eq!(
+            addresses[1].as_ref().unwrap().to_string(),
+            "ben@example.com"
+        );
+        assert_eq!(
+            addresses[2],
+            Err(("Carl <carl>".to_string(), EmailError::MissingAt))
+        );
+    }
+}
@@ -2,16 +2,22 @@
 #![feature(bool_to_option)]
 #![feature(backtrace)]
 
-use anyhow::{ensure, Context, Result};
+use anyhow::{Context, Result};
 use directories::ProjectDirs;
 use log::info;
 use self_update::{cargo_crate_version, Status::*};
-use std::{
-    convert::TryInto,
-    process::{exit, Command},
-};
-
+use std::process::{exit, Command};
+
+pub mod attendance;
+pub mod collation;
+pub mod course_list;
+pub mod email;
+pub mod output;
+pub mod phone;
 pub mod preferences;
+pub mod reference;
+#[cfg(test)]
+mod testing;
 pub mod workbook;
 
 pub trait Column {
@@ -20,16 +26,7 @@ pub trait Column {
 
 impl Column for &str {
     fn try_into_index(&self) -> Result<usize> {
-        ensure!(!self.is_empty(), "Column is empty");
-
-        let index = self
-            .chars()
-            .filter(char::is_ascii_alphabetic)
-            .map(|c| c.to_digit(36).expect("char is expected to be alphabetic"))
-            .fold(0u32, |acc, x| acc * 26 + x - 9)
-            - 1;
-
-        index.try_into().context("Could not convert to usize")
+        Ok(reference::column_index(self)?)
     }
 }
 
@@ -39,6 +36,17 @@ impl Column for String {
     }
 }
 
+/// Inverse of [`Column`] turning a zero based index into column letters
+pub trait ColumnName {
+    fn to_column_name(&self) -> String;
+}
+
+impl ColumnName for usize {
+    fn to_column_name(&self) -> String {
+        reference::column_name(*self)
+    }
+}
+
 pub fn get_proj_dirs() -> Result<ProjectDirs> {
     ProjectDirs::from("com", "chronophylos", "sir").context("No valid home directory found")
 }
@@ -91,5 +99,39 @@ mod tests {
         assert_eq!("W".try_into_index().unwrap(), 22);
         assert_eq!("AA".try_into_index().unwrap(), 26);
         assert_eq!("CY".try_into_index().unwrap(), 102);
+        assert!("A1B".try_into_index().is_err());
+        assert!("cy".try_into_index().is_err());
+    }
+
+    #[test]
+    fn column_name_round_trip() {
+        assert_eq!(22.to_column_name(), "W");
+        assert_eq!(26.to_column_name(), "AA");
+        assert_eq!(102.to_column_name(), "CY");
+
+        // every name of up to three letters is either a valid column which
+        // converts back into the same name or beyond the last column `XFD`
+        let letters: Vec<String> = (b'A'..=b'Z').map(|c| (c as char).to_string()).collect();
+        let mut names = letters.clone();
+        for length in 1..3 {
+            let longer: Vec<String> = names
+                .iter()
+                .filter(|name| name.len() == length)
+                .flat_map(|name| letters.iter().map(move |c| format!("{}{}", name, c)))
+                .collect();
+            names.extend(longer);
+        }
+
+        let mut columns = 0;
+        for name in names {
+            match name.as_str().try_into_index() {
+                Ok(index) => {
+                    assert_eq!(index.to_column_name(), name);
+                    columns += 1;
+                }
+                Err(_) => assert!(name.as_str() > "XFD" && name.len() == 3),
+            }
+        }
+        assert_eq!(columns, reference::MAX_COLUMNS);
     }
 }
@@ -0,0 +1,115 @@
+use super::{Cell, CourseListWriter, OutputError, Table};
+use crate::preferences::{CsvEncoding, CsvOptions, CsvQuoting};
+use csv::{QuoteStyle, Terminator, WriterBuilder};
+use encoding_rs::WINDOWS_1252;
+use std::{fs::write, iter::once, path::Path};
+
+#[derive(Debug)]
+pub struct CsvWriter {
+    options: CsvOptions,
+}
+
+impl CsvWriter {
+    pub fn new(options: CsvOptions) -> Self {
+        Self { options }
+    }
+
+    /// Fails on characters of the record in row `row` of the file which can not
+    /// be encoded
+    fn check_record(&self, row: usize, record: &[String]) -> Result<(), OutputError> {
+        if self.options.encoding != CsvEncoding::Windows1252 {
+            return Ok(());
+        }
+
+        let mut buffer = [0; 4];
+        match record
+            .iter()
+            .flat_map(|field| field.chars())
+            .find(|c| WINDOWS_1252.encode(c.encode_utf8(&mut buffer)).2)
+        {
+            Some(character) => Err(OutputError::UnmappableCharacter { row, character }),
+            None => Ok(()),
+        }
+    }
+
+    /// Text of `cell` with a decimal comma if fields are separated by
+    /// semicolons as by Excel with a german locale which reads a decimal point
+    /// as text or date
+    fn field(&self, cell: &Cell) -> String {
+        match cell {
+            Cell::Number(_) | Cell::Currency(_) if self.options.delimiter == ';' => {
+                cell.to_string().replace('.', ",")
+            }
+            Cell::Formula(_, value) => self.field(value),
+            cell => cell.to_string(),
+        }
+    }
+
+    fn encode(&self, data: Vec<u8>) -> Vec<u8> {
+        match self.options.encoding {
+            CsvEncoding::Utf8 => data,
+            CsvEncoding::Utf8Bom => [&b"\xEF\xBB\xBF"[..], &data].concat(),
+            CsvEncoding::Windows1252 => {
+                // all fields are written from strings so the data is valid utf-8
+                // and every character was checked to be encodable
+                let text = String::from_utf8_lossy(&data);
+                WINDOWS_1252.encode(&text).0.into_owned()
+            }
+        }
+    }
+}
+
+impl CourseListWriter for CsvWriter {
+    fn supports_sheets(&self) -> bool {
+        false
+    }
+
+    fn write(&self, path: &Path, tables: &[Table]) -> Result<(), OutputError> {
+        let delimiter = self.options.delimiter;
+        if !delimiter.is_ascii() || matches!(delimiter, '"' | '\r' | '\n') {
+            return Err(OutputError::InvalidDelimiter(delimiter));
+        }
+
+        let mut writer = WriterBuilder::new()
+            .delimiter(delimiter as u8)
+            .quote_style(match self.options.quoting {
+                CsvQuoting::Necessary => QuoteStyle::Necessary,
+                CsvQuoting::Always => QuoteStyle::Always,
+                CsvQuoting::NonNumeric => QuoteStyle::NonNumeric,
+                CsvQuoting::Never => QuoteStyle::Never,
+            })
+            // excel expects windows line endings
+            .terminator(Terminator::CRLF)
+            .from_writer(Vec::new());
+
+        // row of the file starting at 1
+        let mut row = 0;
+        for table in tables {
+            let header: Vec<String> = table
+                .columns
+                .iter()
+                .map(|column| column.title.clone())
+                .collect();
+            // totals and summary would be read as participants
+            let rows = table
+                .rows
+                .iter()
+                .map(|cells| cells.iter().map(|cell| self.field(cell)).collect());
+
+            for record in once(header).chain(rows) {
+                row += 1;
+                self.check_record(row, &record)?;
+
+                writer
+                    .write_record(record)
+                    .map_err(|err| OutputError::WriteCsv(err))?;
+            }
+        }
+
+        let data = writer
+            .into_inner()
+            .map_err(|err| OutputError::WriteFile(err.into_error()))?;
+
+        write(path, self.encode(data)).map_err(|err| OutputError::WriteFile(err))
+    }
+}
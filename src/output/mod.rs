@@ -0,0 +1,258 @@
+//! Output formats of course lists
+//!
+//! Course lists are converted into format independent [`Table`]s first which
+//! are then rendered by a [`CourseListWriter`] chosen at runtime. Other formats
+//! are added by implementing [`CourseListWriter`].
+
+use crate::preferences::{CsvOptions, OutputFormat, PageSetup};
+use std::{fmt, io, path::Path};
+use thiserror::Error;
+use xlsxwriter::XlsxError;
+
+mod csv;
+mod xlsx;
+
+pub use self::csv::CsvWriter;
+pub use self::xlsx::XlsxWriter;
+
+#[derive(Debug, Error)]
+pub enum OutputError {
+    #[error("Could not add a new worksheet to workbook: {0}")]
+    AddWorksheet(#[source] XlsxError),
+
+    #[error("Could not write header row: {0}")]
+    WriteHeaderRow(#[source] XlsxError),
+
+    #[error("Could not write data row: {0}")]
+    WriteEntryRow(#[source] XlsxError),
+
+    #[error("Could not set column format: {0}")]
+    SetColumn(#[source] XlsxError),
+
+    #[error("Could not set up the printed page: {0}")]
+    PageSetup(#[source] XlsxError),
+
+    #[error("Could not close workbook: {0}")]
+    CloseWorkbook(#[source] XlsxError),
+
+    #[error("Could not write csv record: {0}")]
+    WriteCsv(#[source] ::csv::Error),
+
+    #[error("Could not write file: {0}")]
+    WriteFile(#[source] io::Error),
+
+    #[error("Delimiter {0:?} is not an ASCII character other than a quote or line break")]
+    InvalidDelimiter(char),
+
+    #[error("Row {row} contains `{character}` which can not be written in Windows-1252")]
+    UnmappableCharacter { row: usize, character: char },
+}
+
+/// A single cell of a table
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Number(f64),
+    /// Amount in euro
+    Currency(f64),
+    Text(String),
+    /// Spreadsheet formula like `=SUM(F2:F9)` and its current value
+    Formula(String, Box<Cell>),
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cell::Number(number) => write!(f, "{}", number),
+            Cell::Currency(amount) => write!(f, "{:.2}", amount),
+            Cell::Text(text) => write!(f, "{}", text),
+            Cell::Formula(_, value) => write!(f, "{}", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TableColumn {
+    pub title: String,
+    /// Width in characters as used by spreadsheet applications
+    pub width: f64,
+    pub centered: bool,
+}
+
+impl TableColumn {
+    pub fn new(title: &str, width: f64) -> Self {
+        Self {
+            title: title.to_string(),
+            width,
+            centered: false,
+        }
+    }
+
+    pub fn centered(mut self) -> Self {
+        self.centered = true;
+        self
+    }
+}
+
+/// Format independent representation of a written course list
+#[derive(Debug, Clone)]
+pub struct Table {
+    /// Name of the sheet, writers choose a default if there is none
+    pub name: Option<String>,
+    /// Title printed in the page header, e.g. the group of all participants
+    pub title: Option<String>,
+    pub columns: Vec<TableColumn>,
+    pub rows: Vec<Vec<Cell>>,
+    /// Rows summing up the table written below the rows
+    ///
+    /// Only written by formats which are not read as data like XLSX.
+    pub totals: Vec<Vec<Cell>>,
+    /// Rows written below the totals after an empty row, like the totals only
+    /// by formats which are not read as data
+    pub summary: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    /// Rows, totals and summary separated by an empty row with their kind
+    pub fn all_rows(&self) -> impl Iterator<Item = (RowKind, &[Cell])> {
+        let gap = (!self.summary.is_empty()).then_some((RowKind::Gap, &[][..]));
+
+        self.rows
+            .iter()
+            .map(|cells| (RowKind::Entry, &cells[..]))
+            .chain(self.totals.iter().map(|cells| (RowKind::Total, &cells[..])))
+            .chain(gap)
+            .chain(
+                self.summary
+                    .iter()
+                    .map(|cells| (RowKind::Summary, &cells[..])),
+            )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowKind {
+    Entry,
+    Total,
+    /// Empty row in front of the summary
+    Gap,
+    Summary,
+}
+
+pub trait CourseListWriter {
+    /// Whether more than one table can be written to a single file
+    fn supports_sheets(&self) -> bool;
+
+    /// Writes `tables` to a new file at `path`
+    fn write(&self, path: &Path, tables: &[Table]) -> Result<(), OutputError>;
+}
+
+/// Returns the writer for `format` which has to be resolved already
+///
+/// CSV files are written with `csv` and XLSX files with `page`.
+pub fn writer_for(
+    format: OutputFormat,
+    csv: &CsvOptions,
+    page: PageSetup,
+) -> Box<dyn CourseListWriter> {
+    match format {
+        OutputFormat::Csv => Box::new(CsvWriter::new(csv.clone())),
+        OutputFormat::Xlsx | OutputFormat::Auto => Box::new(XlsxWriter::new(page)),
+    }
+}
+
+/// Maximum length of a sheet name allowed by Excel
+const SHEET_NAME_LENGTH: usize = 31;
+
+/// Turns `name` into a sheet name accepted by Excel which is not in `taken`
+///
+/// The returned name is added to `taken`.
+pub fn unique_sheet_name(name: &str, taken: &mut Vec<String>) -> String {
+    let name: String = name
+        .chars()
+        .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+        .collect();
+    let name = name.trim().trim_matches('\'');
+    let name = if name.is_empty() { "Gruppe" } else { name };
+
+    unique_name(name, SHEET_NAME_LENGTH, taken)
+}
+
+/// Turns `name` into a file name which is not in `taken`
+///
+/// The returned name is added to `taken`.
+pub fn unique_file_name(name: &str, taken: &mut Vec<String>) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let name = name.trim().trim_matches('.');
+    let name = if name.is_empty() { "Gruppe" } else { name };
+
+    unique_name(name, usize::MAX, taken)
+}
+
+fn unique_name(name: &str, max_length: usize, taken: &mut Vec<String>) -> String {
+    let is_taken = |candidate: &str, taken: &[String]| {
+        taken
+            .iter()
+            .any(|name| name.to_lowercase() == candidate.to_lowercase())
+    };
+
+    let mut candidate: String = name.chars().take(max_length).collect();
+    let mut counter = 1;
+
+    while is_taken(&candidate, taken) {
+        counter += 1;
+        let suffix = format!(" ({})", counter);
+        candidate = name
+            .chars()
+            .take(max_length.saturating_sub(suffix.chars().count()))
+            .chain(suffix.chars())
+            .collect();
+    }
+
+    taken.push(candidate.clone());
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sheet_names() {
+        let mut taken = Vec::new();
+
+        assert_eq!(unique_sheet_name("Bronze [Mo]", &mut taken), "Bronze Mo");
+        assert_eq!(unique_sheet_name("bronze mo", &mut taken), "bronze mo (2)");
+        assert_eq!(unique_sheet_name("'?*'", &mut taken), "Gruppe");
+        assert_eq!(
+            unique_sheet_name("Seepferdchen Montag 16:00 bis 17:00 Uhr", &mut taken),
+            "Seepferdchen Montag 1600 bis 17"
+        );
+        assert_eq!(
+            unique_sheet_name("Seepferdchen Montag 16:00 bis 17:30 Uhr", &mut taken),
+            "Seepferdchen Montag 1600 bi (2)"
+        );
+        assert!(taken.iter().all(|name| name.chars().count() <= 31));
+    }
+
+    #[test]
+    fn file_names() {
+        let mut taken = Vec::new();
+
+        assert_eq!(
+            unique_file_name("Mo 16:00/17:00", &mut taken),
+            "Mo 16_00_17_00"
+        );
+        assert_eq!(
+            unique_file_name("Mo 16:00/17:00", &mut taken),
+            "Mo 16_00_17_00 (2)"
+        );
+        assert_eq!(unique_file_name("..", &mut taken), "Gruppe");
+    }
+}
@@ -0,0 +1,134 @@
+use super::{Cell, CourseListWriter, OutputError, RowKind, Table};
+use crate::preferences::{Orientation, PageSetup};
+use std::path::Path;
+use xlsxwriter::{FormatAlignment, FormatBorder, GridLines, Workbook, Worksheet};
+
+/// Number format of amounts in euro
+const CURRENCY_FORMAT: &str = "#,##0.00 €;-#,##0.00 €";
+
+#[derive(Debug, Default)]
+pub struct XlsxWriter {
+    page: PageSetup,
+}
+
+impl XlsxWriter {
+    pub fn new(page: PageSetup) -> Self {
+        Self { page }
+    }
+
+    fn set_up_page(&self, sheet: &mut Worksheet, table: &Table) -> Result<(), OutputError> {
+        match self.page.orientation {
+            Orientation::Portrait => sheet.set_portrait(),
+            Orientation::Landscape => sheet.set_landscape(),
+        }
+        if self.page.fit_to_width {
+            // a height of 0 uses as many pages as needed
+            sheet.fit_to_pages(1, 0);
+        }
+        if self.page.repeat_header {
+            sheet
+                .repeat_rows(0, 0)
+                .map_err(|err| OutputError::PageSetup(err))?;
+        }
+        if self.page.header_footer {
+            let title = table.title.as_deref().unwrap_or_default();
+            sheet
+                .set_header(&format!("&L{}&R&D", title.replace('&', "&&")))
+                .map_err(|err| OutputError::PageSetup(err))?;
+            sheet
+                .set_footer("&CSeite &P von &N")
+                .map_err(|err| OutputError::PageSetup(err))?;
+        }
+        sheet.gridlines(match self.page.gridlines {
+            true => GridLines::ShowAllGridLines,
+            false => GridLines::ShowScreenGridLines,
+        });
+        if self.page.freeze_header {
+            sheet.freeze_panes(1, 0);
+        }
+
+        Ok(())
+    }
+}
+
+impl CourseListWriter for XlsxWriter {
+    fn supports_sheets(&self) -> bool {
+        true
+    }
+
+    fn write(&self, path: &Path, tables: &[Table]) -> Result<(), OutputError> {
+        let workbook = Workbook::new(&path.to_string_lossy());
+
+        let header_format = workbook
+            .add_format()
+            .set_align(FormatAlignment::Center)
+            .set_border_bottom(FormatBorder::Medium)
+            .set_bold();
+
+        let currency_format = workbook.add_format().set_num_format(CURRENCY_FORMAT);
+        let total_format = workbook.add_format().set_bold();
+        let total_currency_format = workbook
+            .add_format()
+            .set_num_format(CURRENCY_FORMAT)
+            .set_bold();
+
+        let centered_format = workbook.add_format().set_align(FormatAlignment::Center);
+
+        for table in tables {
+            let mut sheet = workbook
+                .add_worksheet(table.name.as_deref())
+                .map_err(|err| OutputError::AddWorksheet(err))?;
+            self.set_up_page(&mut sheet, table)?;
+
+            for (col, column) in table.columns.iter().enumerate() {
+                let col = col as u16;
+                let format = column.centered.then_some(&centered_format);
+
+                sheet
+                    .set_column(col, col, column.width, format)
+                    .map_err(|err| OutputError::SetColumn(err))?;
+                sheet
+                    .write_string(0, col, &column.title, Some(&header_format))
+                    .map_err(|err| OutputError::WriteHeaderRow(err))?;
+            }
+
+            for (row, (kind, cells)) in table.all_rows().enumerate() {
+                let row = row as u32 + 1;
+                let (format, currency_format) = match kind {
+                    RowKind::Total => (Some(&total_format), &total_currency_format),
+                    _ => (None, &currency_format),
+                };
+
+                for (col, cell) in cells.iter().enumerate() {
+                    let col = col as u16;
+
+                    match cell {
+                        Cell::Formula(formula, value) => match value.as_ref() {
+                            Cell::Currency(amount) => sheet.write_formula_num(
+                                row,
+                                col,
+                                formula,
+                                Some(currency_format),
+                                *amount,
+                            ),
+                            Cell::Number(number) => {
+                                sheet.write_formula_num(row, col, formula, format, *number)
+                            }
+                            _ => sheet.write_formula(row, col, formula, format),
+                        },
+                        Cell::Number(number) => sheet.write_number(row, col, *number, format),
+                        Cell::Currency(amount) => {
+                            sheet.write_number(row, col, *amount, Some(currency_format))
+                        }
+                        Cell::Text(text) => sheet.write_string(row, col, text, format),
+                    }
+                    .map_err(|err| OutputError::WriteEntryRow(err))?;
+                }
+            }
+        }
+
+        workbook
+            .close()
+            .map_err(|err| OutputError::CloseWorkbook(err))
+    }
+}
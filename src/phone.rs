@@ -0,0 +1,310 @@
+//! Telephone numbers as written into the booking sheet
+//!
+//! Numbers without a country code are taken to be German numbers.
+
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+/// Country code numbers without one belong to
+pub const DEFAULT_COUNTRY_CODE: &str = "49";
+
+/// Longest number allowed by E.164 without the plus sign
+const MAX_DIGITS: usize = 15;
+
+/// Shortest national number (without the trunk prefix 0) that is accepted
+const MIN_NATIONAL_DIGITS: usize = 6;
+
+/// Country codes of countries dialing national numbers with a trunk prefix 0
+/// which is sometimes written after the country code like `+49 (0)7531`
+///
+/// In other countries like Italy a leading 0 is part of the number.
+const TRUNK_PREFIX_COUNTRY_CODES: &[&str] = &["31", "32", "33", "41", "43", "44", "46", "49", "90"];
+
+/// Longest German number without the trunk prefix 0
+const MAX_NATIONAL_DIGITS: usize = 11;
+
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum PhoneError {
+    #[error("Telephone number is empty")]
+    Empty,
+
+    #[error("Invalid character `{0}` in telephone number")]
+    InvalidCharacter(char),
+
+    #[error("Telephone number has no area code")]
+    MissingAreaCode,
+
+    #[error("Telephone number is too short")]
+    TooShort,
+
+    #[error("Telephone number is too long")]
+    TooLong,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhoneKind {
+    Mobile,
+    Landline,
+    /// Numbers of other countries are not classified
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhoneNumber {
+    country_code: String,
+    /// Digits of the number without country code and trunk prefix
+    national: String,
+    /// Length of the area code if it was separated in the written number
+    area_code: Option<usize>,
+}
+
+impl PhoneNumber {
+    pub fn country_code(&self) -> &str {
+        &self.country_code
+    }
+
+    pub fn is_german(&self) -> bool {
+        self.country_code == DEFAULT_COUNTRY_CODE
+    }
+
+    /// Classifies German numbers by the prefixes 15, 16 and 17 of mobile networks
+    pub fn kind(&self) -> PhoneKind {
+        if !self.is_german() {
+            PhoneKind::Unknown
+        } else if ["15", "16", "17"]
+            .iter()
+            .any(|prefix| self.national.starts_with(prefix))
+        {
+            PhoneKind::Mobile
+        } else {
+            PhoneKind::Landline
+        }
+    }
+
+    /// Number in E.164 format like `+497531123456`
+    pub fn e164(&self) -> String {
+        format!("+{}{}", self.country_code, self.national)
+    }
+
+    /// Number as dialed within Germany like `07531 123456`
+    ///
+    /// The area code is only separated if it was in the written number or the
+    /// number is a mobile number. Numbers of other countries are written
+    /// with their country code.
+    pub fn national(&self) -> String {
+        let area_code = match self.kind() {
+            PhoneKind::Mobile => Some(self.area_code.unwrap_or(3)),
+            _ => self.area_code,
+        };
+
+        let number = match area_code {
+            Some(len) if len < self.national.len() => {
+                format!("{} {}", &self.national[..len], &self.national[len..])
+            }
+            _ => self.national.clone(),
+        };
+
+        if self.is_german() {
+            format!("0{}", number)
+        } else {
+            format!("+{} {}", self.country_code, number)
+        }
+    }
+}
+
+impl FromStr for PhoneNumber {
+    type Err = PhoneError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(PhoneError::Empty);
+        }
+
+        // digits and the positions of separators between them
+        let mut digits = String::new();
+        let mut separators = Vec::new();
+        for (i, c) in s.char_indices() {
+            match c {
+                '0'..='9' => digits.push(c),
+                '+' if i == 0 => {}
+                ' ' | '-' | '/' | '(' | ')' | '.' => separators.push(digits.len()),
+                c => return Err(PhoneError::InvalidCharacter(c)),
+            }
+        }
+
+        let (country_code, national) = if s.starts_with('+') || digits.starts_with("00") {
+            let digits = if s.starts_with('+') {
+                &digits[..]
+            } else {
+                &digits[2..]
+            };
+            let code_len = country_code_len(digits);
+            if digits.len() <= code_len {
+                return Err(PhoneError::TooShort);
+            }
+
+            let (country_code, national) = digits.split_at(code_len);
+            // drop a trunk prefix written like `+49 (0)7531`
+            let national = match TRUNK_PREFIX_COUNTRY_CODES.contains(&country_code) {
+                true => national.strip_prefix('0').unwrap_or(national),
+                false => national,
+            };
+
+            (country_code.to_string(), national.to_string())
+        } else if let Some(national) = digits.strip_prefix('0') {
+            (DEFAULT_COUNTRY_CODE.to_string(), national.to_string())
+        } else {
+            return Err(PhoneError::MissingAreaCode);
+        };
+
+        if national.len() < MIN_NATIONAL_DIGITS {
+            return Err(PhoneError::TooShort);
+        }
+        if country_code.len() + national.len() > MAX_DIGITS {
+            return Err(PhoneError::TooLong);
+        }
+
+        // separators up to the trunk prefix or country code end no area code
+        let skipped = digits.len() - national.len();
+        let area_code = separators
+            .into_iter()
+            .find(|&pos| pos > skipped && pos < digits.len())
+            .map(|pos| pos - skipped);
+
+        Ok(Self {
+            country_code,
+            national,
+            area_code,
+        })
+    }
+}
+
+impl fmt::Display for PhoneNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.national())
+    }
+}
+
+/// Length of the country code at the start of `digits`
+///
+/// Country codes are prefix free so the length only depends on the first digits.
+fn country_code_len(digits: &str) -> usize {
+    const TWO_DIGITS: &[&str] = &[
+        "20", "27", "30", "31", "32", "33", "34", "36", "39", "40", "41", "43", "44", "45", "46",
+        "47", "48", "49", "51", "52", "53", "54", "55", "56", "57", "58", "60", "61", "62", "63",
+        "64", "65", "66", "81", "82", "84", "86", "90", "91", "92", "93", "94", "95", "98",
+    ];
+
+    if digits.starts_with('1') || digits.starts_with('7') {
+        1
+    } else if TWO_DIGITS.iter().any(|code| digits.starts_with(code)) {
+        2
+    } else {
+        3
+    }
+}
+
+/// Whether the digits of a number stored as number, which lost its leading
+/// `+`, `00` or `0`, are an international number
+///
+/// German numbers are never longer than 11 digits without their trunk prefix,
+/// so only longer numbers are taken as international if they are valid as
+/// such. Shorter ones starting with 49 are German numbers of area codes like
+/// 0491 as well.
+pub fn is_international_digits(digits: &str) -> bool {
+    digits.len() > MAX_NATIONAL_DIGITS && format!("+{}", digits).parse::<PhoneNumber>().is_ok()
+}
+
+/// Splits a cell holding several numbers on line breaks, semicolons and commas
+///
+/// Every part is parsed on its own, the written text is kept for invalid ones.
+pub fn parse_numbers(s: &str) -> Vec<Result<PhoneNumber, (String, PhoneError)>> {
+    s.split(&['\n', '\r', ';', ','][..])
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse().map_err(|err| (part.to_string(), err)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> PhoneNumber {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn german_numbers() {
+        let number = parse("07531 / 12 34 56");
+        assert_eq!(number.e164(), "+497531123456");
+        assert_eq!(number.national(), "07531 123456");
+        assert_eq!(number.kind(), PhoneKind::Landline);
+
+        assert_eq!(parse("075311234").national(), "075311234");
+        assert_eq!(parse("0049 7531 1234").national(), "07531 1234");
+        assert_eq!(parse("+49 (0)7531 1234").e164(), "+4975311234");
+        assert_eq!(parse("+49 (0)7531 1234").national(), "07531 1234");
+
+        let mobile = parse("01511234567");
+        assert_eq!(mobile.kind(), PhoneKind::Mobile);
+        assert_eq!(mobile.national(), "0151 1234567");
+        assert_eq!(mobile.e164(), "+491511234567");
+    }
+
+    #[test]
+    fn foreign_numbers() {
+        let number = parse("+41 44 123 45 67");
+        assert_eq!(number.country_code(), "41");
+        assert_eq!(number.kind(), PhoneKind::Unknown);
+        assert_eq!(number.e164(), "+41441234567");
+        assert_eq!(number.national(), "+41 44 1234567");
+        assert_eq!(parse("+41 (0)44 123 45 67").e164(), "+41441234567");
+
+        // Italian numbers keep their leading 0
+        let rome = parse("+39 06 1234 5678");
+        assert_eq!(rome.e164(), "+390612345678");
+        assert_eq!(rome.national(), "+39 06 12345678");
+        assert_eq!(parse("0039 06 12345678").e164(), "+390612345678");
+    }
+
+    #[test]
+    fn digits_without_prefix() {
+        assert!(is_international_digits("491511234567"));
+        assert!(is_international_digits("393123456789"));
+        assert!(!is_international_digits("7531123456"));
+        assert!(!is_international_digits("15112345678"));
+        assert!(!is_international_digits("4921123456"));
+        assert!(!is_international_digits("4912"));
+    }
+
+    #[test]
+    fn invalid_numbers() {
+        assert_eq!("".parse::<PhoneNumber>(), Err(PhoneError::Empty));
+        assert_eq!(
+            "7531123".parse::<PhoneNumber>(),
+            Err(PhoneError::MissingAreaCode)
+        );
+        assert_eq!("0751 12".parse::<PhoneNumber>(), Err(PhoneError::TooShort));
+        assert_eq!(
+            "0751 123 x".parse::<PhoneNumber>(),
+            Err(PhoneError::InvalidCharacter('x'))
+        );
+        assert_eq!(
+            "+49 7531 1234567890123".parse::<PhoneNumber>(),
+            Err(PhoneError::TooLong)
+        );
+    }
+
+    #[test]
+    fn several_numbers() {
+        let numbers = parse_numbers("07531 123456\r\n0151 1234567; Oma");
+        assert_eq!(numbers.len(), 3);
+        assert_eq!(numbers[1].as_ref().unwrap().kind(), PhoneKind::Mobile);
+        assert_eq!(
+            numbers[2],
+            Err(("Oma".to_string(), PhoneError::InvalidCharacter('O')))
+        );
+    }
+}
@@ -1,12 +1,15 @@
-use crate::get_proj_dirs;
+use crate::{attendance::Holiday, get_proj_dirs};
 use anyhow::{ensure, Context, Result};
+use chrono::{NaiveDate, Weekday};
 use log::{info, warn};
 use ron::{de::from_reader, ser::to_writer};
 use serde::{Deserialize, Serialize};
 use std::{
+    fmt,
     fs::{create_dir_all, File},
     io::{BufReader, BufWriter},
     path::{Path, PathBuf},
+    str::FromStr,
 };
 use thiserror::Error;
 
@@ -30,12 +33,653 @@ pub struct Preferences {
     pub src_sheet: String,
     pub src_column: String,
     pub dest_path: String,
-    #[serde(default)]
+    /// Additional columns as `(name, column)` of older versions, only read
+    /// to migrate them into `auxiliary_columns`
+    #[serde(default, skip_serializing)]
     pub auxiliaries: Option<Vec<(String, String)>>,
+    /// Additional columns appended to the course list in this order
+    #[serde(default)]
+    pub auxiliary_columns: Vec<AuxiliaryColumn>,
+    #[serde(default)]
+    pub sort: Sorting,
+    #[serde(default)]
+    pub columns: ColumnMapping,
+    /// Base columns of the course list in the order they are written
+    #[serde(default)]
+    pub base_columns: BaseColumns,
+    #[serde(default)]
+    pub dest_format: OutputFormat,
+    #[serde(default)]
+    pub csv: CsvOptions,
+    #[serde(default)]
+    pub page: PageSetup,
+    #[serde(default)]
+    pub split_groups: GroupSplit,
+    /// Leave out rows which can not be read instead of failing
+    #[serde(default)]
+    pub skip_invalid_rows: bool,
+    #[serde(default)]
+    pub phone_format: PhoneFormat,
+    /// Leave out invalid email addresses, e.g. for mailing lists
+    #[serde(default)]
+    pub exclude_invalid_emails: bool,
+    /// Write the price of every participant with totals
+    #[serde(default)]
+    pub show_price: bool,
+    /// Column letters of the price in the booking sheet
+    #[serde(default)]
+    pub price_column: String,
+    /// Leave out the summary below the list, e.g. for importing the list
+    #[serde(default)]
+    pub hide_summary: bool,
+    #[serde(default)]
+    pub list_kind: ListKind,
+    #[serde(default)]
+    pub attendance: AttendanceOptions,
+}
+
+/// Kind of list written for the participants
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ListKind {
+    /// Contact details of the participants
+    Contacts,
+    /// Sign-in sheet per group with a column for every lesson
+    Attendance,
+}
+
+impl Default for ListKind {
+    fn default() -> Self {
+        Self::Contacts
+    }
+}
+
+impl FromStr for ListKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "contacts" => Ok(Self::Contacts),
+            "attendance" => Ok(Self::Attendance),
+            _ => Err(format!("Unknown list kind `{}`", s)),
+        }
+    }
+}
+
+/// Lessons of the course for attendance lists
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AttendanceOptions {
+    /// First day of the course, the first lesson is on the following `weekday`
+    pub start: Option<NaiveDate>,
+    pub weekday: Weekday,
+    pub lessons: usize,
+    /// Days without lessons
+    pub holidays: Vec<Holiday>,
+}
+
+impl Default for AttendanceOptions {
+    fn default() -> Self {
+        Self {
+            start: None,
+            weekday: Weekday::Mon,
+            lessons: 10,
+            holidays: Vec::new(),
+        }
+    }
+}
+
+/// Header labels used to locate the columns of the booking sheet
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ColumnMapping {
+    /// Row of the header in the sheet starting at 1, detected if not set
+    pub header_row: Option<usize>,
+    pub id: String,
+    pub name: String,
+    pub telephone: String,
+    pub email: String,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        Self {
+            header_row: None,
+            id: "Kundennummer".to_string(),
+            name: "Name".to_string(),
+            telephone: "Telefon".to_string(),
+            email: "E-Mail".to_string(),
+        }
+    }
+}
+
+/// Field of a participant written as a base column of the course list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum BaseField {
+    Id,
+    Group,
+    Name,
+    Telephone,
+    Email,
+}
+
+impl BaseField {
+    pub const ALL: [BaseField; 5] = [
+        Self::Id,
+        Self::Group,
+        Self::Name,
+        Self::Telephone,
+        Self::Email,
+    ];
+
+    /// Header of the column unless another one is configured
+    pub fn default_label(self) -> &'static str {
+        match self {
+            Self::Id => "Kundennummer",
+            Self::Group => "Gruppe",
+            Self::Name => "Name",
+            Self::Telephone => "Telefon",
+            Self::Email => "E-Mail",
+        }
+    }
+}
+
+impl fmt::Display for BaseField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Id => write!(f, "Customer id"),
+            Self::Group => write!(f, "Group"),
+            Self::Name => write!(f, "Name"),
+            Self::Telephone => write!(f, "Telephone"),
+            Self::Email => write!(f, "Email"),
+        }
+    }
+}
+
+impl FromStr for BaseField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "id" => Ok(Self::Id),
+            "group" => Ok(Self::Group),
+            "name" => Ok(Self::Name),
+            "telephone" | "phone" => Ok(Self::Telephone),
+            "email" | "e-mail" => Ok(Self::Email),
+            _ => Err(format!("Unknown column `{}`", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct BaseColumn {
+    pub field: BaseField,
+    /// Header of the column in the course list
+    pub label: String,
+}
+
+impl BaseColumn {
+    pub fn new(field: BaseField) -> Self {
+        Self {
+            field,
+            label: field.default_label().to_string(),
+        }
+    }
+}
+
+/// Selection and order of the base columns of the course list
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct BaseColumns(pub Vec<BaseColumn>);
+
+impl BaseColumns {
+    pub fn iter(&self) -> impl Iterator<Item = &BaseColumn> {
+        self.0.iter()
+    }
+
+    pub fn contains(&self, field: BaseField) -> bool {
+        self.iter().any(|column| column.field == field)
+    }
+}
+
+impl Default for BaseColumns {
+    fn default() -> Self {
+        Self(
+            BaseField::ALL
+                .iter()
+                .copied()
+                .map(BaseColumn::new)
+                .collect(),
+        )
+    }
+}
+
+impl FromStr for BaseColumns {
+    type Err = String;
+
+    /// Parses a list like `name, id=Nr., telephone` with optional labels
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut columns: Vec<BaseColumn> = Vec::new();
+
+        for part in s.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            let mut parts = part.splitn(2, '=');
+            let field: BaseField = parts.next().unwrap_or_default().trim().parse()?;
+            if columns.iter().any(|column| column.field == field) {
+                return Err(format!("Column `{}` is listed twice", part));
+            }
+
+            columns.push(match parts.next() {
+                Some(label) => BaseColumn {
+                    field,
+                    label: label.trim().to_string(),
+                },
+                None => BaseColumn::new(field),
+            });
+        }
+
+        Ok(Self(columns))
+    }
+}
+
+/// Order of the participants in the course list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Sorting {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+/// What participants are sorted by, ties are ordered by their customer id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SortKey {
+    Name,
+    /// Group and then name
+    GroupName,
+    /// Last word of the name, for names written like `Anna Huber`
+    Surname,
+    Id,
+    /// Additional column by its position starting at 0
+    Auxiliary(usize),
+}
+
+impl SortKey {
+    /// The key following this one, used to cycle through all keys of a list
+    /// with `auxiliaries` additional columns
+    pub fn next(self, auxiliaries: usize) -> Self {
+        match self {
+            Self::Name => Self::GroupName,
+            Self::GroupName => Self::Surname,
+            Self::Surname => Self::Id,
+            Self::Id if auxiliaries > 0 => Self::Auxiliary(0),
+            Self::Auxiliary(index) if index + 1 < auxiliaries => Self::Auxiliary(index + 1),
+            Self::Id | Self::Auxiliary(_) => Self::Name,
+        }
+    }
+}
+
+impl Default for SortKey {
+    fn default() -> Self {
+        Self::Name
+    }
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name => write!(f, "name"),
+            Self::GroupName => write!(f, "group"),
+            Self::Surname => write!(f, "surname"),
+            Self::Id => write!(f, "id"),
+            Self::Auxiliary(index) => write!(f, "aux:{}", index + 1),
+        }
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_ascii_lowercase();
+        if let Some(position) = lower.strip_prefix("aux:") {
+            return match position.parse::<usize>() {
+                Ok(position) if position > 0 => Ok(Self::Auxiliary(position - 1)),
+                _ => Err(format!(
+                    "Expected the position of an additional column in `{}`",
+                    s
+                )),
+            };
+        }
+
+        match lower.as_str() {
+            "name" => Ok(Self::Name),
+            "group" => Ok(Self::GroupName),
+            "surname" => Ok(Self::Surname),
+            "id" => Ok(Self::Id),
+            _ => Err(format!("Unknown sort key `{}`", s)),
+        }
+    }
+}
+
+/// Column of the booking sheet copied into the course list as is
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AuxiliaryColumn {
+    /// Header of the column in the course list
+    pub name: String,
+    /// Column letters in the booking sheet
+    pub column: String,
+    pub format: AuxiliaryFormat,
+}
+
+impl AuxiliaryColumn {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_empty() && self.column.is_empty()
+    }
+}
+
+/// Leaves out empty additional columns and points `sorting` at the same column
+/// among the remaining ones
+///
+/// Sorting by an empty or missing column falls back to sorting by name.
+pub fn used_auxiliary_columns(
+    columns: &[AuxiliaryColumn],
+    sorting: Sorting,
+) -> (Vec<AuxiliaryColumn>, Sorting) {
+    let key = match sorting.key {
+        SortKey::Auxiliary(index) => match columns.get(index) {
+            Some(column) if !column.is_empty() => SortKey::Auxiliary(
+                columns[..index]
+                    .iter()
+                    .filter(|column| !column.is_empty())
+                    .count(),
+            ),
+            _ => SortKey::Name,
+        },
+        key => key,
+    };
+
+    (
+        columns
+            .iter()
+            .filter(|column| !column.is_empty())
+            .cloned()
+            .collect(),
+        Sorting { key, ..sorting },
+    )
+}
+
+/// How the cells of an additional column are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum AuxiliaryFormat {
+    Text,
+    Number,
+    /// Dates are written like `24.12.2020`
+    Date,
+}
+
+impl AuxiliaryFormat {
+    /// The format following this one, used to cycle through all formats
+    pub fn next(self) -> Self {
+        match self {
+            Self::Text => Self::Number,
+            Self::Number => Self::Date,
+            Self::Date => Self::Text,
+        }
+    }
+}
+
+impl Default for AuxiliaryFormat {
+    fn default() -> Self {
+        Self::Text
+    }
+}
+
+impl fmt::Display for AuxiliaryFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text => write!(f, "Text"),
+            Self::Number => write!(f, "Number"),
+            Self::Date => write!(f, "Date"),
+        }
+    }
+}
+
+impl FromStr for AuxiliaryFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "number" => Ok(Self::Number),
+            "date" => Ok(Self::Date),
+            _ => Err(format!("Unknown column format `{}`", s)),
+        }
+    }
+}
+
+/// File format of the generated course list
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum OutputFormat {
+    /// Choose the format by the extension of the destination
+    Auto,
+    Xlsx,
+    Csv,
+}
+
+impl OutputFormat {
+    /// Replaces `Auto` with the format matching the extension of `path`
+    pub fn resolve<P>(self, path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        match self {
+            Self::Auto => match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+                Some(ext) if ext.eq_ignore_ascii_case("csv") => Self::Csv,
+                _ => Self::Xlsx,
+            },
+            format => format,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Xlsx | Self::Auto => "xlsx",
+        }
+    }
+}
+
+impl Default for OutputFormat {
+    fn default() -> Self {
+        Self::Auto
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "xlsx" => Ok(Self::Xlsx),
+            "csv" => Ok(Self::Csv),
+            _ => Err(format!("Unknown output format `{}`", s)),
+        }
+    }
+}
+
+/// How a course list is split by the groups of its participants
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum GroupSplit {
+    /// Write all groups into a single list
+    Off,
+    /// Write every group to its own worksheet
+    Sheets,
+    /// Write every group to its own file inside the destination directory
+    Files,
+}
+
+impl Default for GroupSplit {
+    fn default() -> Self {
+        Self::Off
+    }
+}
+
+impl FromStr for GroupSplit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(Self::Off),
+            "sheets" => Ok(Self::Sheets),
+            "files" => Ok(Self::Files),
+            _ => Err(format!("Unknown group split `{}`", s)),
+        }
+    }
+}
+
+/// How telephone numbers are written to course lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PhoneFormat {
+    /// As dialed within Germany like `07531 123456`
+    National,
+    /// E.164 like `+497531123456`
+    International,
+}
+
+impl Default for PhoneFormat {
+    fn default() -> Self {
+        Self::National
+    }
+}
+
+impl FromStr for PhoneFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "national" => Ok(Self::National),
+            "international" | "e164" | "e.164" => Ok(Self::International),
+            _ => Err(format!("Unknown phone format `{}`", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CsvOptions {
+    /// Field delimiter, has to be an ASCII character
+    ///
+    /// Numbers are written with a decimal comma if it is `;`.
+    pub delimiter: char,
+    pub quoting: CsvQuoting,
+    pub encoding: CsvEncoding,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        // the defaults are what Excel expects with a german locale
+        Self {
+            delimiter: ';',
+            quoting: CsvQuoting::Necessary,
+            encoding: CsvEncoding::Utf8Bom,
+        }
+    }
+}
+
+/// How XLSX course lists are printed
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PageSetup {
+    pub orientation: Orientation,
+    /// Scale the columns to the width of a page
+    pub fit_to_width: bool,
+    /// Print the header row on every page
+    pub repeat_header: bool,
+    /// Print group and date in the page header and page numbers in the footer
+    pub header_footer: bool,
+    pub gridlines: bool,
+    /// Keep the header row visible while scrolling
+    pub freeze_header: bool,
+}
+
+impl Default for PageSetup {
+    fn default() -> Self {
+        Self {
+            orientation: Orientation::Portrait,
+            fit_to_width: true,
+            repeat_header: true,
+            header_footer: true,
+            gridlines: true,
+            freeze_header: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+impl FromStr for Orientation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "portrait" => Ok(Self::Portrait),
+            "landscape" => Ok(Self::Landscape),
+            _ => Err(format!("Unknown orientation `{}`", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum CsvQuoting {
+    /// Quote fields containing delimiters, quotes or line breaks
+    Necessary,
+    Always,
+    /// Quote all fields which are not numbers
+    NonNumeric,
+    Never,
+}
+
+impl FromStr for CsvQuoting {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "necessary" => Ok(Self::Necessary),
+            "always" => Ok(Self::Always),
+            "non-numeric" => Ok(Self::NonNumeric),
+            "never" => Ok(Self::Never),
+            _ => Err(format!("Unknown quoting style `{}`", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum CsvEncoding {
+    Utf8,
+    /// UTF-8 with a byte order mark so Excel detects the encoding
+    Utf8Bom,
+    Windows1252,
+}
+
+impl FromStr for CsvEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Ok(Self::Utf8),
+            "utf-8-bom" | "utf8-bom" => Ok(Self::Utf8Bom),
+            "windows-1252" | "cp1252" => Ok(Self::Windows1252),
+            _ => Err(format!("Unknown encoding `{}`", s)),
+        }
+    }
 }
 
 impl Preferences {
-    fn from_path<P>(path: P) -> Result<Self>
+    pub fn from_path<P>(path: P) -> Result<Self>
     where
         P: AsRef<Path>,
     {
@@ -45,7 +689,27 @@ impl Preferences {
         let file = File::open(path).with_context(|| PreferenceError::OpenFile(path.to_owned()))?;
         let reader = BufReader::new(file);
 
-        Ok(from_reader(reader).context(PreferenceError::Deserialize)?)
+        let mut prefs: Self = from_reader(reader).context(PreferenceError::Deserialize)?;
+        prefs.migrate();
+
+        Ok(prefs)
+    }
+
+    /// Moves settings of older versions to their current place
+    fn migrate(&mut self) {
+        if let Some(auxiliaries) = self.auxiliaries.take() {
+            if self.auxiliary_columns.is_empty() {
+                self.auxiliary_columns = auxiliaries
+                    .into_iter()
+                    .map(|(name, column)| AuxiliaryColumn {
+                        name,
+                        column,
+                        format: AuxiliaryFormat::Text,
+                    })
+                    .filter(|aux| !aux.is_empty())
+                    .collect();
+            }
+        }
     }
 
     fn write<P>(&self, path: P) -> Result<()>
@@ -67,6 +731,10 @@ fn get_preferences_path() -> Result<PathBuf> {
 }
 
 pub async fn load_preferences() -> Result<Preferences> {
+    read_preferences()
+}
+
+pub fn read_preferences() -> Result<Preferences> {
     let path = get_preferences_path().context(PreferenceError::NoPath)?;
     info!("Loading preferences from `{}`", path.display());
 
@@ -95,3 +763,107 @@ pub async fn store_preferences(prefs: Preferences) -> Result<()> {
         .write(path.clone())
         .with_context(|| format!("Could not store preferences at `{}`", path.display()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_auxiliaries() {
+        let mut prefs: Preferences = ron::de::from_str(
+            r#"(
+                src_path: "",
+                src_sheet: "",
+                src_column: "",
+                dest_path: "",
+                auxiliaries: Some([("Notiz", "M"), ("", "")]),
+            )"#,
+        )
+        .unwrap();
+        prefs.migrate();
+
+        assert_eq!(prefs.auxiliaries, None);
+        assert_eq!(
+            prefs.auxiliary_columns,
+            vec![AuxiliaryColumn {
+                name: "Notiz".to_string(),
+                column: "M".to_string(),
+                format: AuxiliaryFormat::Text,
+            }]
+        );
+
+        let written = ron::ser::to_string(&prefs).unwrap();
+        assert!(!written.contains("auxiliaries:"));
+        assert_eq!(prefs.base_columns, BaseColumns::default());
+    }
+
+    #[test]
+    fn base_columns() {
+        let columns: BaseColumns = "name, id=Nr., telephone".parse().unwrap();
+        assert_eq!(
+            columns.0,
+            vec![
+                BaseColumn::new(BaseField::Name),
+                BaseColumn {
+                    field: BaseField::Id,
+                    label: "Nr.".to_string(),
+                },
+                BaseColumn::new(BaseField::Telephone),
+            ]
+        );
+        assert!(!columns.contains(BaseField::Email));
+
+        assert!("name, email, name".parse::<BaseColumns>().is_err());
+        assert!("price".parse::<BaseColumns>().is_err());
+    }
+
+    #[test]
+    fn sort_keys() {
+        for key in &[
+            SortKey::Name,
+            SortKey::GroupName,
+            SortKey::Surname,
+            SortKey::Id,
+            SortKey::Auxiliary(2),
+        ] {
+            assert_eq!(key.to_string().parse(), Ok(*key));
+        }
+        assert!("aux:0".parse::<SortKey>().is_err());
+
+        assert_eq!(SortKey::Id.next(0), SortKey::Name);
+        assert_eq!(SortKey::Id.next(2), SortKey::Auxiliary(0));
+        assert_eq!(SortKey::Auxiliary(1).next(2), SortKey::Name);
+    }
+
+    #[test]
+    fn leave_out_empty_auxiliaries() {
+        let column = |name: &str, column: &str| AuxiliaryColumn {
+            name: name.to_string(),
+            column: column.to_string(),
+            format: AuxiliaryFormat::Text,
+        };
+        let columns = vec![column("", ""), column("Alter", "M"), column("", "")];
+        let sorting = |key| Sorting {
+            key,
+            descending: true,
+        };
+
+        let (used, sort) = used_auxiliary_columns(&columns, sorting(SortKey::Auxiliary(1)));
+        assert_eq!(used, vec![column("Alter", "M")]);
+        assert_eq!(sort, sorting(SortKey::Auxiliary(0)));
+        for key in &[
+            SortKey::Auxiliary(0),
+            SortKey::Auxiliary(2),
+            SortKey::Auxiliary(3),
+        ] {
+            assert_eq!(
+                used_auxiliary_columns(&columns, sorting(*key)).1,
+                sorting(SortKey::Name)
+            );
+        }
+        assert_eq!(
+            used_auxiliary_columns(&columns, sorting(SortKey::Id)).1,
+            sorting(SortKey::Id)
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Cell references in A1 notation like `C12`, `$C$12`, `P:U` or `A31:L400`
+
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+/// Number of columns in a worksheet, the last one being `XFD`
+pub const MAX_COLUMNS: usize = 16_384;
+
+/// Number of rows in a worksheet
+pub const MAX_ROWS: usize = 1_048_576;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ReferenceError {
+    #[error("Reference is empty")]
+    Empty,
+
+    #[error("Invalid character `{character}` in `{reference}`")]
+    InvalidCharacter { reference: String, character: char },
+
+    #[error("`{0}` has no column letters")]
+    MissingColumn(String),
+
+    #[error("`{0}` has no row number")]
+    MissingRow(String),
+
+    #[error("Column `{0}` is beyond the last column XFD")]
+    ColumnOutOfRange(String),
+
+    #[error("Row `{0}` is not between 1 and 1048576")]
+    RowOutOfRange(String),
+
+    #[error("Range `{0}` ends before it starts")]
+    ReversedRange(String),
+}
+
+/// Converts column letters like `CY` into a zero based index
+///
+/// Only upper case letters are accepted.
+pub fn column_index(letters: &str) -> Result<usize, ReferenceError> {
+    if letters.is_empty() {
+        return Err(ReferenceError::Empty);
+    }
+
+    let mut index = 0usize;
+
+    for c in letters.chars() {
+        if !c.is_ascii_uppercase() {
+            return Err(ReferenceError::InvalidCharacter {
+                reference: letters.to_string(),
+                character: c,
+            });
+        }
+
+        index = index * 26 + (c as usize - 'A' as usize + 1);
+        if index > MAX_COLUMNS {
+            return Err(ReferenceError::ColumnOutOfRange(letters.to_string()));
+        }
+    }
+
+    Ok(index - 1)
+}
+
+/// Converts a zero based column index into its letters, e.g. 102 into `CY`
+pub fn column_name(index: usize) -> String {
+    let mut name = Vec::new();
+    let mut rest = index + 1;
+
+    while rest > 0 {
+        rest -= 1;
+        name.push(b'A' + (rest % 26) as u8);
+        rest /= 26;
+    }
+
+    name.reverse();
+    String::from_utf8(name).expect("column letters are ascii")
+}
+
+/// Splits an optional `$` marker off the start of `s`
+fn absolute(s: &str) -> (bool, &str) {
+    match s.strip_prefix('$') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    }
+}
+
+/// Parses a column with an optional `$` marker
+fn parse_column(s: &str) -> Result<(usize, bool), ReferenceError> {
+    let (fixed, letters) = absolute(s);
+    if letters.is_empty() {
+        return Err(ReferenceError::MissingColumn(s.to_string()));
+    }
+
+    Ok((column_index(letters)?, fixed))
+}
+
+/// Reference to a single cell, row and column are zero based
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellRef {
+    pub row: usize,
+    pub column: usize,
+    pub absolute_row: bool,
+    pub absolute_column: bool,
+}
+
+impl CellRef {
+    pub fn new(row: usize, column: usize) -> Self {
+        Self {
+            row,
+            column,
+            absolute_row: false,
+            absolute_column: false,
+        }
+    }
+}
+
+impl FromStr for CellRef {
+    type Err = ReferenceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ReferenceError::Empty);
+        }
+
+        // the column ends where the row (or its `$` marker) starts
+        let split = s
+            .char_indices()
+            .skip(1)
+            .find(|(_, c)| *c == '$' || c.is_ascii_digit())
+            .map_or(s.len(), |(i, _)| i);
+        let (column, row) = s.split_at(split);
+
+        let (column, absolute_column) = parse_column(column).map_err(|err| match err {
+            ReferenceError::InvalidCharacter { character, .. } => {
+                ReferenceError::InvalidCharacter {
+                    reference: s.to_string(),
+                    character,
+                }
+            }
+            ReferenceError::MissingColumn(_) => ReferenceError::MissingColumn(s.to_string()),
+            err => err,
+        })?;
+
+        let (absolute_row, digits) = absolute(row);
+        if digits.is_empty() {
+            return Err(ReferenceError::MissingRow(s.to_string()));
+        }
+        if let Some(character) = digits.chars().find(|c| !c.is_ascii_digit()) {
+            return Err(ReferenceError::InvalidCharacter {
+                reference: s.to_string(),
+                character,
+            });
+        }
+
+        let row: usize = digits
+            .parse()
+            .map_err(|_| ReferenceError::RowOutOfRange(digits.to_string()))?;
+        if row == 0 || row > MAX_ROWS {
+            return Err(ReferenceError::RowOutOfRange(digits.to_string()));
+        }
+
+        Ok(Self {
+            row: row - 1,
+            column,
+            absolute_row,
+            absolute_column,
+        })
+    }
+}
+
+impl fmt::Display for CellRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}{}",
+            if self.absolute_column { "$" } else { "" },
+            column_name(self.column),
+            if self.absolute_row { "$" } else { "" },
+            self.row + 1
+        )
+    }
+}
+
+/// Range of whole columns like `P:U`, a single column `P` is a range as well
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl ColumnRange {
+    /// Indices of all columns in the range
+    pub fn columns(&self) -> impl Iterator<Item = usize> {
+        self.start..=self.end
+    }
+}
+
+impl FromStr for ColumnRange {
+    type Err = ReferenceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bounds = s.splitn(2, ':');
+        let start = parse_column(bounds.next().unwrap_or_default())?.0;
+        let end = match bounds.next() {
+            Some(end) => parse_column(end)?.0,
+            None => start,
+        };
+
+        if end < start {
+            return Err(ReferenceError::ReversedRange(s.to_string()));
+        }
+
+        Ok(Self { start, end })
+    }
+}
+
+impl fmt::Display for ColumnRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", column_name(self.start), column_name(self.end))
+    }
+}
+
+/// Rectangular range of cells like `A31:L400`, a single cell is a range as well
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeRef {
+    pub start: CellRef,
+    pub end: CellRef,
+}
+
+impl FromStr for RangeRef {
+    type Err = ReferenceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bounds = s.splitn(2, ':');
+        let start: CellRef = bounds.next().unwrap_or_default().parse()?;
+        let end = match bounds.next() {
+            Some(end) => end.parse()?,
+            None => start,
+        };
+
+        if end.row < start.row || end.column < start.column {
+            return Err(ReferenceError::ReversedRange(s.to_string()));
+        }
+
+        Ok(Self { start, end })
+    }
+}
+
+impl fmt::Display for RangeRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.start, self.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cell_reference() {
+        assert_eq!("C12".parse(), Ok(CellRef::new(11, 2)));
+        assert_eq!(
+            "$C$12".parse(),
+            Ok(CellRef {
+                absolute_row: true,
+                absolute_column: true,
+                ..CellRef::new(11, 2)
+            })
+        );
+        assert_eq!("$C$12".parse::<CellRef>().unwrap().to_string(), "$C$12");
+        assert_eq!("XFD1048576".parse(), Ok(CellRef::new(1_048_575, 16_383)));
+
+        for invalid in &[
+            "", "C", "12", "A1B", "c12", "C0", "C1048577", "XFE1", "C$", "$$C1",
+        ] {
+            assert!(invalid.parse::<CellRef>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!("P:U".parse(), Ok(ColumnRange { start: 15, end: 20 }));
+        assert_eq!("P".parse(), Ok(ColumnRange { start: 15, end: 15 }));
+        assert_eq!(
+            "U:P".parse::<ColumnRange>(),
+            Err(ReferenceError::ReversedRange("U:P".to_string()))
+        );
+        assert!("P:".parse::<ColumnRange>().is_err());
+        assert!("P1:U".parse::<ColumnRange>().is_err());
+
+        let range: RangeRef = "A31:L400".parse().unwrap();
+        assert_eq!(range.start, CellRef::new(30, 0));
+        assert_eq!(range.end, CellRef::new(399, 11));
+        assert_eq!(range.to_string(), "A31:L400");
+        assert!("L400:A31".parse::<RangeRef>().is_err());
+    }
+
+    #[test]
+    fn column_letters() {
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(16_383), "XFD");
+        assert!(column_index("a").is_err());
+        assert!(column_index("A1").is_err());
+    }
+}
@@ -0,0 +1,43 @@
+//! Fixtures shared by the tests of several modules
+
+use crate::course_list::{CourseEntry, CustomerId};
+use calamine::{DataType, Range};
+
+/// Sheet whose used range starts at `first_column` and is as wide as the
+/// longest row, empty strings are empty cells
+pub fn sheet_range(first_column: u32, rows: &[&[&str]]) -> Range<DataType> {
+    let width = rows.iter().map(|cells| cells.len()).max().unwrap_or(1) as u32;
+    let mut range = Range::new(
+        (0, first_column),
+        (rows.len() as u32 - 1, first_column + width - 1),
+    );
+
+    for (row, cells) in rows.iter().enumerate() {
+        for (col, cell) in cells.iter().enumerate() {
+            if !cell.is_empty() {
+                range.set_value(
+                    (row as u32, first_column + col as u32),
+                    DataType::String(cell.to_string()),
+                );
+            }
+        }
+    }
+
+    range
+}
+
+/// Participant of `group` read from row 2 with the group in column B and
+/// without contact details, price or additional columns
+pub fn course_entry(id: CustomerId, group: &str, name: &str) -> CourseEntry {
+    CourseEntry {
+        row: 2,
+        id,
+        group: group.to_string(),
+        columns: vec![1],
+        name: name.to_string(),
+        telephone: Vec::new(),
+        email: Vec::new(),
+        price: None,
+        auxiliaries: Vec::new(),
+    }
+}
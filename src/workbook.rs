@@ -1,5 +1,6 @@
+use crate::phone::is_international_digits;
 use calamine::{open_workbook_auto, DataType, Range, Reader, Sheets};
-
+use chrono::NaiveDate;
 use std::{fmt::Debug, path::Path};
 use thiserror::Error;
 
@@ -46,3 +47,216 @@ impl WorkbookManager {
         })
     }
 }
+
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum CellError {
+    #[error("Cell is empty")]
+    Empty,
+
+    #[error("Cell contains the error {0}")]
+    Formula(String),
+
+    #[error("Expected a whole number but found `{0}`")]
+    NotAnInteger(String),
+
+    #[error("Expected a number but found `{0}`")]
+    NotANumber(String),
+
+    #[error("Expected a date but found `{0}`")]
+    NotADate(String),
+}
+
+/// Formats of dates written as text
+///
+/// `%Y` accepts two digit years as well so `%y` has to be tried first.
+const DATE_FORMATS: &[&str] = &["%d.%m.%y", "%d.%m.%Y", "%Y-%m-%d"];
+
+/// Parses a date written like `24.12.2020`, `24.12.20` or `2020-12-24`
+pub fn parse_date(text: &str) -> Option<NaiveDate> {
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text.trim(), format).ok())
+}
+
+/// Typed access to the value of a cell
+pub trait CellValue {
+    /// Whole number written as number or text
+    fn int(&self) -> Result<i64, CellError>;
+
+    /// Number written as number or as text with a decimal point or comma
+    fn number(&self) -> Result<f64, CellError>;
+
+    /// Text of the cell with whole numbers written without a fraction
+    fn text(&self) -> Result<String, CellError>;
+
+    /// Text of a cell holding telephone numbers
+    ///
+    /// Numbers written as number lost their leading `0` or `+` which is
+    /// restored.
+    fn phone_text(&self) -> Result<String, CellError>;
+
+    /// Date written as date or as text like `24.12.2020`
+    fn date(&self) -> Result<NaiveDate, CellError>;
+}
+
+/// Turns an empty cell into `None` instead of an error
+pub trait Optional<T> {
+    fn optional(self) -> Result<Option<T>, CellError>;
+}
+
+impl<T> Optional<T> for Result<T, CellError> {
+    fn optional(self) -> Result<Option<T>, CellError> {
+        match self {
+            Ok(value) => Ok(Some(value)),
+            Err(CellError::Empty) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Whole number of a float without fraction
+fn whole(float: f64) -> Option<i64> {
+    (float.fract() == 0.0 && float.abs() < i64::MAX as f64).then_some(float as i64)
+}
+
+impl CellValue for DataType {
+    fn int(&self) -> Result<i64, CellError> {
+        match self {
+            DataType::Int(int) => Ok(*int),
+            DataType::Float(float) => {
+                whole(*float).ok_or_else(|| CellError::NotAnInteger(float.to_string()))
+            }
+            DataType::String(text) if text.trim().is_empty() => Err(CellError::Empty),
+            DataType::String(text) => text
+                .trim()
+                .parse()
+                .map_err(|_| CellError::NotAnInteger(text.clone())),
+            DataType::Bool(b) => Err(CellError::NotAnInteger(b.to_string())),
+            DataType::Error(err) => Err(CellError::Formula(err.to_string())),
+            DataType::Empty => Err(CellError::Empty),
+        }
+    }
+
+    fn number(&self) -> Result<f64, CellError> {
+        match self {
+            DataType::Int(int) => Ok(*int as f64),
+            DataType::Float(float) => Ok(*float),
+            DataType::String(text) if text.trim().is_empty() => Err(CellError::Empty),
+            DataType::String(text) => text
+                .trim()
+                .replace(',', ".")
+                .parse()
+                .map_err(|_| CellError::NotANumber(text.clone())),
+            DataType::Bool(b) => Err(CellError::NotANumber(b.to_string())),
+            DataType::Error(err) => Err(CellError::Formula(err.to_string())),
+            DataType::Empty => Err(CellError::Empty),
+        }
+    }
+
+    fn text(&self) -> Result<String, CellError> {
+        match self {
+            DataType::Int(int) => Ok(int.to_string()),
+            DataType::Float(float) => Ok(whole(*float)
+                .map(|int| int.to_string())
+                .unwrap_or_else(|| float.to_string())),
+            DataType::String(text) if text.trim().is_empty() => Err(CellError::Empty),
+            DataType::String(text) => Ok(text.trim().to_string()),
+            DataType::Bool(b) => Ok(b.to_string()),
+            DataType::Error(err) => Err(CellError::Formula(err.to_string())),
+            DataType::Empty => Err(CellError::Empty),
+        }
+    }
+
+    fn phone_text(&self) -> Result<String, CellError> {
+        match self {
+            DataType::Int(_) | DataType::Float(_) => {
+                let digits = self.text()?;
+                match is_international_digits(&digits) {
+                    true => Ok(format!("+{}", digits)),
+                    false => Ok(format!("0{}", digits)),
+                }
+            }
+            _ => self.text(),
+        }
+    }
+
+    fn date(&self) -> Result<NaiveDate, CellError> {
+        match self {
+            DataType::Int(_) | DataType::Float(_) => self
+                .as_date()
+                .ok_or_else(|| CellError::NotADate(self.to_string())),
+            DataType::String(text) if text.trim().is_empty() => Err(CellError::Empty),
+            DataType::String(text) => {
+                parse_date(text).ok_or_else(|| CellError::NotADate(text.clone()))
+            }
+            DataType::Bool(b) => Err(CellError::NotADate(b.to_string())),
+            DataType::Error(err) => Err(CellError::Formula(err.to_string())),
+            DataType::Empty => Err(CellError::Empty),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calamine::CellErrorType;
+
+    #[test]
+    fn typed_cells() {
+        let text = |s: &str| DataType::String(s.to_string());
+
+        assert_eq!(DataType::Float(42.0).int(), Ok(42));
+        assert_eq!(text(" 42 ").int(), Ok(42));
+        assert_eq!(
+            DataType::Float(4.2).int(),
+            Err(CellError::NotAnInteger("4.2".to_string()))
+        );
+        assert_eq!(
+            text("x").int(),
+            Err(CellError::NotAnInteger("x".to_string()))
+        );
+        assert_eq!(
+            DataType::Error(CellErrorType::NA).int(),
+            Err(CellError::Formula("#N/A".to_string()))
+        );
+
+        assert_eq!(text("12,5").number(), Ok(12.5));
+        assert_eq!(
+            text("zwölf").number(),
+            Err(CellError::NotANumber("zwölf".to_string()))
+        );
+
+        assert_eq!(DataType::Float(12.0).text(), Ok("12".to_string()));
+        assert_eq!(text(" Anna ").text(), Ok("Anna".to_string()));
+        assert_eq!(text("  ").text(), Err(CellError::Empty));
+        assert_eq!(DataType::Empty.text().optional(), Ok(None));
+
+        assert_eq!(
+            DataType::Float(7531123.0).phone_text(),
+            Ok("07531123".to_string())
+        );
+        assert_eq!(
+            DataType::Float(491511234567.0).phone_text(),
+            Ok("+491511234567".to_string())
+        );
+        assert_eq!(
+            DataType::Float(4921123456.0).phone_text(),
+            Ok("04921123456".to_string())
+        );
+        assert_eq!(
+            DataType::Int(15112345678).phone_text(),
+            Ok("015112345678".to_string())
+        );
+        assert_eq!(text("07531 123").phone_text(), Ok("07531 123".to_string()));
+
+        let christmas = NaiveDate::from_ymd(2020, 12, 24);
+        assert_eq!(DataType::Float(44189.0).date(), Ok(christmas));
+        assert_eq!(text("24.12.2020").date(), Ok(christmas));
+        assert_eq!(text("24.12.20").date(), Ok(christmas));
+        assert_eq!(text("2020-12-24").date(), Ok(christmas));
+        assert_eq!(
+            text("24/12/2020").date(),
+            Err(CellError::NotADate("24/12/2020".to_string()))
+        );
+    }
+}